@@ -0,0 +1,112 @@
+extern crate rshellitems;
+use rshellitems::shellitem::{ShellContent};
+use rshellitems::shelllist::{ShellList};
+use rshellitems::borrowed::{ShellListRef};
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+// Compares decoding the file entry names of the same shell item list with the
+// owned parser against the borrowing parser. Run with `cargo bench`.
+const ITERATIONS: usize = 100000;
+
+fn owned_names(buffer: &[u8]) -> Vec<String> {
+    let shell_list = ShellList::new(Cursor::new(buffer)).unwrap();
+    let mut names = Vec::new();
+    for entry in shell_list.iter() {
+        let data = match entry.get_item() {
+            Some(item) => item.data.as_ref(),
+            None => None
+        };
+        if let Some(data) = data {
+            if let ShellContent::FileEntry(ref file_entry) = data.content {
+                names.push(file_entry.get_name(true).to_string());
+            }
+        }
+    }
+    names
+}
+
+fn borrowed_names(buffer: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    for item in ShellListRef::new(buffer) {
+        let item = item.unwrap();
+        let is_file_entry = match item.get_class_type() {
+            Some(class_type) => class_type.get_major() == 0x30,
+            None => false
+        };
+        if !is_file_entry {
+            continue;
+        }
+        if let Some(name) = item.get_long_name().or(item.get_name()) {
+            names.push(name.decode().unwrap());
+        }
+    }
+    names
+}
+
+fn as_secs(elapsed: Duration) -> f64 {
+    elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+fn report(label: &str, bytes: usize, elapsed: Duration) {
+    let seconds = as_secs(elapsed);
+    println!(
+        "{}: {} bytes in {:.3}s ({:.2} MB/s)",
+        label, bytes, seconds, bytes as f64 / seconds / (1024.0 * 1024.0)
+    );
+}
+
+fn main() {
+    // example_003 from shelllist_examples without the list size
+    let buffer: &[u8] = &[
+        0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+        0x2B,0x30,0x30,0x9D,0x19,0x00,0x2F,0x43,0x3A,0x5C,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x64,0x00,0x31,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x50,0x72,0x6F,0x67,0x72,
+        0x61,0x6D,0x20,0x46,0x69,0x6C,0x65,0x73,0x00,0x48,0x00,0x08,0x00,0x04,0x00,0xEF,
+        0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x50,0x00,0x72,0x00,0x6F,0x00,0x67,0x00,0x72,0x00,0x61,0x00,0x6D,
+        0x00,0x20,0x00,0x46,0x00,0x69,0x00,0x6C,0x00,0x65,0x00,0x73,0x00,0x00,0x00,0x1C,
+        0x00,0x6E,0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x4D,
+        0x69,0x63,0x72,0x6F,0x73,0x6F,0x66,0x74,0x20,0x4F,0x66,0x66,0x69,0x63,0x65,0x00,
+        0x00,0x4E,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x4D,0x00,0x69,0x00,0x63,
+        0x00,0x72,0x00,0x6F,0x00,0x73,0x00,0x6F,0x00,0x66,0x00,0x74,0x00,0x20,0x00,0x4F,
+        0x00,0x66,0x00,0x66,0x00,0x69,0x00,0x63,0x00,0x65,0x00,0x00,0x00,0x20,0x00,0x56,
+        0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x4F,0x66,0x66,
+        0x69,0x63,0x65,0x31,0x35,0x00,0x00,0x3E,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x4F,0x00,0x66,0x00,0x66,0x00,0x69,0x00,0x63,0x00,0x65,0x00,0x31,0x00,0x35,
+        0x00,0x00,0x00,0x18,0x00,0x62,0x00,0x32,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x4D,0x53,0x41,0x43,0x43,0x45,0x53,0x53,0x2E,0x45,0x58,0x45,0x00,
+        0x00,0x46,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x4D,0x00,0x53,0x00,0x41,
+        0x00,0x43,0x00,0x43,0x00,0x45,0x00,0x53,0x00,0x53,0x00,0x2E,0x00,0x45,0x00,0x58,
+        0x00,0x45,0x00,0x00,0x00,0x1C,0x00,0x00,0x00
+    ];
+    let total_bytes = buffer.len() * ITERATIONS;
+
+    // both parsers have to produce the same names for the timings to compare
+    assert_eq!(owned_names(buffer),borrowed_names(buffer));
+
+    let mut owned_bytes = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        owned_bytes += owned_names(buffer).iter().map(|name| name.len()).sum::<usize>();
+    }
+    report("owned", total_bytes, start.elapsed());
+
+    let mut borrowed_bytes = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        borrowed_bytes += borrowed_names(buffer).iter().map(|name| name.len()).sum::<usize>();
+    }
+    report("borrowed", total_bytes, start.elapsed());
+
+    // keep the results alive so the loops are not optimized away
+    println!("name bytes: owned {}, borrowed {}", owned_bytes, borrowed_bytes);
+}
@@ -0,0 +1,479 @@
+use byteorder::{ByteOrder, LittleEndian};
+use errors::{ShellItemError};
+use shellitem::{ShellItem, ClassType};
+use shelllist::{ShellList};
+use extension_blocks::{ExtensionBlock};
+use utils;
+use std::io::Cursor;
+use std::io::{Error,ErrorKind};
+
+// Borrowing parsers for bulk scanning of memory images and hive dumps. Names and
+// raw data are slices of the input buffer and are only decoded or copied when
+// converted to the owned types.
+
+const FILE_ENTRY_NAME_OFFSET: usize = 14;
+const VOLUME_NAME_OFFSET: usize = 3;
+const VOLUME_NAME_SIZE: usize = 20;
+
+fn truncated(offset: usize, needed: usize, available: usize) -> ShellItemError {
+    ShellItemError::from(
+        Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("Need {} bytes at offset {} but only {} are available", needed, offset, available)
+        )
+    )
+}
+
+fn read_u16_at(buffer: &[u8], offset: usize) -> Result<u16,ShellItemError> {
+    if buffer.len() < offset + 2 {
+        return Err(truncated(offset, 2, buffer.len().saturating_sub(offset)));
+    }
+    Ok(LittleEndian::read_u16(&buffer[offset..]))
+}
+
+// Index of the first null byte at or after `start`
+fn find_null_u8(buffer: &[u8], start: usize) -> Option<usize> {
+    if start > buffer.len() {
+        return None;
+    }
+    buffer[start..].iter()
+        .position(|b| *b == 0x00)
+        .map(|index| start + index)
+}
+
+// Index of the first null utf16 char at or after `start`
+fn find_null_u16(buffer: &[u8], start: usize) -> Option<usize> {
+    if start > buffer.len() {
+        return None;
+    }
+    buffer[start..].chunks(2)
+        .position(|c| c.len() == 2 && c[0] == 0x00 && c[1] == 0x00)
+        .map(|index| start + index * 2)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum NameRef<'a> {
+    Ascii(&'a [u8]),
+    Utf16(&'a [u8])
+}
+impl<'a> NameRef<'a> {
+    // The encoded name without its null terminator
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match *self {
+            NameRef::Ascii(bytes) => bytes,
+            NameRef::Utf16(bytes) => bytes
+        }
+    }
+
+    pub fn decode(&self) -> Result<String,ShellItemError> {
+        match *self {
+            NameRef::Ascii(bytes) => utils::decode_ascii(bytes),
+            NameRef::Utf16(bytes) => utils::decode_utf16le(bytes)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExtensionBlockRef<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    size: u16
+}
+impl<'a> ExtensionBlockRef<'a> {
+    // `offset` is the start of the block within `buffer`
+    pub fn new(buffer: &'a [u8], offset: usize) -> Result<ExtensionBlockRef<'a>,ShellItemError> {
+        let size = read_u16_at(buffer, offset)?;
+        // size(2), version(2) and signature(4) is the smallest possible block
+        if buffer.len() < offset + size as usize || size < 8 {
+            return Err(truncated(offset, size as usize, buffer.len().saturating_sub(offset)));
+        }
+
+        Ok(
+            ExtensionBlockRef {
+                buffer: buffer,
+                offset: offset,
+                size: size
+            }
+        )
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset as u64
+    }
+
+    pub fn get_size(&self) -> u16 {
+        self.size
+    }
+
+    pub fn get_version(&self) -> u16 {
+        LittleEndian::read_u16(&self.buffer[self.offset + 2..])
+    }
+
+    pub fn get_signature(&self) -> u32 {
+        LittleEndian::read_u32(&self.buffer[self.offset + 4..])
+    }
+
+    pub fn get_raw(&self) -> &'a [u8] {
+        &self.buffer[self.offset..self.offset + self.size as usize]
+    }
+
+    // The block content following the header
+    pub fn get_data(&self) -> &'a [u8] {
+        &self.get_raw()[8..]
+    }
+
+    // The long name of a Beef0004 block
+    pub fn get_file_entry_name(&self) -> Option<NameRef<'a>> {
+        let version = self.get_version();
+        if self.get_signature() != 0xBEEF0004 {
+            return None;
+        }
+        // only these versions have a known layout, see Beef0004::new
+        match version {
+            3 | 7 | 8 | 9 => {},
+            _ => return None
+        }
+
+        // header(8), creation(4), last_access(4), identifier(2) and long_string_size(2)
+        let mut start = 20;
+        if version >= 7 {
+            // unknown1(2), file_reference(8) and unknown2(8)
+            start += 18;
+        }
+        if version >= 8 {
+            start += 4;
+        }
+        if version >= 9 {
+            start += 4;
+        }
+
+        let raw = self.get_raw();
+        find_null_u16(raw, start).map(
+            |end| NameRef::Utf16(&raw[start..end])
+        )
+    }
+
+    pub fn to_extension_block(&self) -> Result<ExtensionBlock,ShellItemError> {
+        let mut reader = Cursor::new(self.buffer);
+        reader.set_position(self.offset as u64);
+        ExtensionBlock::new(&mut reader)
+    }
+}
+
+pub struct ExtensionBlockRefIter<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    end: usize
+}
+impl<'a> Iterator for ExtensionBlockRefIter<'a> {
+    type Item = ExtensionBlockRef<'a>;
+
+    fn next(&mut self) -> Option<ExtensionBlockRef<'a>> {
+        if self.offset + 8 > self.end {
+            return None;
+        }
+
+        let size = LittleEndian::read_u16(&self.buffer[self.offset..]) as usize;
+        if size < 8 || self.offset + size > self.end {
+            // Null block is terminator, anything else is an invalid size
+            if size != 0 {
+                warn!("Extension block at offset {} has an invalid size of {}", self.offset, size);
+            }
+            self.offset = self.end;
+            return None;
+        }
+
+        let block = ExtensionBlockRef {
+            buffer: self.buffer,
+            offset: self.offset,
+            size: size as u16
+        };
+        self.offset += size;
+        Some(block)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShellItemRef<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    size: u16
+}
+impl<'a> ShellItemRef<'a> {
+    // `offset` is the start of the item within `buffer`
+    pub fn new(buffer: &'a [u8], offset: usize) -> Result<ShellItemRef<'a>,ShellItemError> {
+        let size = read_u16_at(buffer, offset)?;
+        if buffer.len() < offset + size as usize {
+            return Err(truncated(offset, size as usize, buffer.len() - offset));
+        }
+
+        Ok(
+            ShellItemRef {
+                buffer: buffer,
+                offset: offset,
+                size: size
+            }
+        )
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset as u64
+    }
+
+    pub fn get_size(&self) -> u16 {
+        self.size
+    }
+
+    pub fn get_raw(&self) -> &'a [u8] {
+        &self.buffer[self.offset..self.offset + self.size as usize]
+    }
+
+    pub fn get_class_type(&self) -> Option<ClassType> {
+        if self.size < 3 {
+            return None;
+        }
+        Some(ClassType::new(self.buffer[self.offset + 2]))
+    }
+
+    // The item data following size(2), class_type(1) and unknown(1)
+    pub fn get_data(&self) -> &'a [u8] {
+        let raw = self.get_raw();
+        if raw.len() < 4 {
+            return &raw[raw.len()..];
+        }
+        &raw[4..]
+    }
+
+    // The primary name of volume and file entry items. File entries hold their
+    // 8.3 name here, see get_long_name.
+    pub fn get_name(&self) -> Option<NameRef<'a>> {
+        let class_type = match self.get_class_type() {
+            Some(class_type) => class_type,
+            None => return None
+        };
+        let raw = self.get_raw();
+
+        match class_type.get_type() {
+            0x2E => None,
+            0x20...0x2F => {
+                // has name flag
+                if class_type.get_minor() & 0x01 == 0 {
+                    return None;
+                }
+                let field_end = if raw.len() < VOLUME_NAME_OFFSET + VOLUME_NAME_SIZE {
+                    raw.len()
+                } else {
+                    VOLUME_NAME_OFFSET + VOLUME_NAME_SIZE
+                };
+                let end = find_null_u8(&raw[..field_end], VOLUME_NAME_OFFSET).unwrap_or(field_end);
+                Some(NameRef::Ascii(&raw[VOLUME_NAME_OFFSET..end]))
+            },
+            0x30...0x3F => {
+                // is unicode flag
+                if class_type.get_minor() & 0x04 > 0 {
+                    find_null_u16(raw, FILE_ENTRY_NAME_OFFSET).map(
+                        |end| NameRef::Utf16(&raw[FILE_ENTRY_NAME_OFFSET..end])
+                    )
+                } else {
+                    find_null_u8(raw, FILE_ENTRY_NAME_OFFSET).map(
+                        |end| NameRef::Ascii(&raw[FILE_ENTRY_NAME_OFFSET..end])
+                    )
+                }
+            },
+            _ => None
+        }
+    }
+
+    // The long name of a file entry item, taken from its Beef0004 extension block
+    pub fn get_long_name(&self) -> Option<NameRef<'a>> {
+        for block in self.extension_blocks() {
+            if let Some(name) = block.get_file_entry_name() {
+                return Some(name);
+            }
+        }
+        None
+    }
+
+    // The extension blocks of a file entry item, empty for other items
+    pub fn extension_blocks(&self) -> ExtensionBlockRefIter<'a> {
+        let start = match self.get_name() {
+            Some(NameRef::Utf16(name)) if self.is_file_entry() => {
+                FILE_ENTRY_NAME_OFFSET + name.len() + 2
+            },
+            Some(NameRef::Ascii(name)) if self.is_file_entry() => {
+                // Add 1 to name length to account for null byte and align to 2 bytes
+                FILE_ENTRY_NAME_OFFSET + name.len() + 1 + (name.len() + 1) % 2
+            },
+            _ => self.size as usize
+        };
+
+        ExtensionBlockRefIter {
+            buffer: self.buffer,
+            offset: self.offset + start,
+            end: self.offset + self.size as usize
+        }
+    }
+
+    fn is_file_entry(&self) -> bool {
+        match self.get_class_type() {
+            Some(class_type) => class_type.get_major() == 0x30,
+            None => false
+        }
+    }
+
+    pub fn to_shell_item(&self) -> Result<ShellItem,ShellItemError> {
+        let mut reader = Cursor::new(self.buffer);
+        reader.set_position(self.offset as u64);
+        ShellItem::new(&mut reader)
+    }
+}
+
+pub struct ShellItemRefIter<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    done: bool
+}
+impl<'a> Iterator for ShellItemRefIter<'a> {
+    type Item = Result<ShellItemRef<'a>,ShellItemError>;
+
+    fn next(&mut self) -> Option<Result<ShellItemRef<'a>,ShellItemError>> {
+        if self.done {
+            return None;
+        }
+
+        let item = match ShellItemRef::new(self.buffer, self.offset) {
+            Ok(item) => item,
+            Err(error) => {
+                self.done = true;
+                return Some(Err(error));
+            }
+        };
+
+        if item.get_size() < 2 {
+            // Null shell item is terminator
+            self.done = true;
+            return None;
+        }
+
+        self.offset += item.get_size() as usize;
+        Some(Ok(item))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShellListRef<'a> {
+    buffer: &'a [u8]
+}
+impl<'a> ShellListRef<'a> {
+    pub fn new(buffer: &'a [u8]) -> ShellListRef<'a> {
+        ShellListRef {
+            buffer: buffer
+        }
+    }
+
+    pub fn iter(&self) -> ShellItemRefIter<'a> {
+        ShellItemRefIter {
+            buffer: self.buffer,
+            offset: 0,
+            done: false
+        }
+    }
+
+    pub fn to_shell_list(&self) -> Result<ShellList,ShellItemError> {
+        ShellList::new(Cursor::new(self.buffer))
+    }
+}
+impl<'a> IntoIterator for ShellListRef<'a> {
+    type Item = Result<ShellItemRef<'a>,ShellItemError>;
+    type IntoIter = ShellItemRefIter<'a>;
+
+    fn into_iter(self) -> ShellItemRefIter<'a> {
+        self.iter()
+    }
+}
+
+#[test]
+fn test_shell_item_ref_names() {
+    let buffer: &[u8] = &[
+        0x64,0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x50,0x72,
+        0x6F,0x67,0x72,0x61,0x6D,0x20,0x46,0x69,0x6C,0x65,0x73,0x00,0x48,0x00,0x08,0x00,
+        0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x2A,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x50,0x00,0x72,0x00,0x6F,0x00,0x67,0x00,0x72,0x00,
+        0x61,0x00,0x6D,0x00,0x20,0x00,0x46,0x00,0x69,0x00,0x6C,0x00,0x65,0x00,0x73,0x00,
+        0x00,0x00,0x1C,0x00,0x00,0x00
+    ];
+
+    let item = ShellListRef::new(buffer).iter().next().unwrap().unwrap();
+    assert_eq!(item.get_size(),0x64);
+    assert_eq!(item.get_name().unwrap().decode().unwrap(),"Program Files");
+    assert_eq!(item.get_long_name().unwrap().decode().unwrap(),"Program Files");
+    assert_eq!(item.extension_blocks().count(),1);
+}
+
+#[test]
+fn test_shell_item_ref_truncated() {
+    // A file entry item of 0x64 bytes cut off after 8 bytes
+    let buffer: &[u8] = &[
+        0x64,0x00,0x31,0x00,0x00,0x00,0x00,0x00
+    ];
+
+    assert!(ShellItemRef::new(buffer, 0).is_err());
+    // not even the size is available
+    assert!(ShellItemRef::new(buffer, 7).is_err());
+    assert!(ShellItemRef::new(buffer, 9).is_err());
+
+    let mut items = ShellListRef::new(buffer).iter();
+    assert!(items.next().unwrap().is_err());
+    assert!(items.next().is_none());
+}
+
+#[test]
+fn test_extension_block_ref_errors() {
+    // A Beef0004 block of 0x48 bytes cut off after its header
+    let buffer: &[u8] = &[
+        0x48,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00
+    ];
+    assert!(ExtensionBlockRef::new(buffer, 0).is_err());
+    assert!(ExtensionBlockRef::new(buffer, 9).is_err());
+
+    // A zero size and a size too small for the header
+    assert!(ExtensionBlockRef::new(&[0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00], 0).is_err());
+    assert!(ExtensionBlockRef::new(&[0x04,0x00,0x00,0x00,0x00,0x00,0x00,0x00], 0).is_err());
+}
+
+#[test]
+fn test_extension_block_ref_larger_than_item() {
+    // A file entry item named "A" holding a block whose size of 0x48 exceeds
+    // the item, followed by more data
+    let buffer: &[u8] = &[
+        0x1A,0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x41,0x00,
+        0x48,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00
+    ];
+
+    let item = ShellItemRef::new(buffer, 0).unwrap();
+    assert_eq!(item.get_name().unwrap().decode().unwrap(),"A");
+    assert!(ExtensionBlockRef::new(item.get_raw(), 16).is_err());
+    assert_eq!(item.extension_blocks().count(),0);
+    assert!(item.get_long_name().is_none());
+}
+
+#[test]
+fn test_shell_item_ref_zero_size() {
+    // A null item ends the list, items too small for a class type have no name
+    let buffer: &[u8] = &[
+        0x00,0x00,0x14,0x00
+    ];
+
+    assert!(ShellListRef::new(buffer).iter().next().is_none());
+
+    let item = ShellItemRef::new(buffer, 0).unwrap();
+    assert_eq!(item.get_size(),0);
+    assert!(item.get_class_type().is_none());
+    assert!(item.get_name().is_none());
+    assert!(item.get_data().is_empty());
+    assert_eq!(item.extension_blocks().count(),0);
+}
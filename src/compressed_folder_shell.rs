@@ -0,0 +1,149 @@
+use errors::{ShellItemError};
+use utils;
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+
+// Set in the byte following the class type when the strings are stored as utf16,
+// otherwise they are ascii
+const HAS_UNICODE_STRINGS: u8 = 0x80;
+// Date strings are stored as fixed size fields of 20 chars, e.g. "01/15/2015  11:01"
+const DATE_STRING_CHARS: usize = 20;
+// Offset of the first date string relative to the start of the item
+const DATE_STRING_OFFSET: usize = 36;
+
+fn decode_string(buffer: &[u8], unicode: bool) -> Result<String,ShellItemError> {
+    if unicode {
+        utils::decode_utf16le(buffer)
+    } else {
+        utils::decode_ascii(buffer)
+    }
+}
+
+// Splits a buffer into its null terminated strings
+fn split_strings(buffer: &[u8], unicode: bool) -> Result<Vec<String>,ShellItemError> {
+    let char_size = if unicode { 2 } else { 1 };
+    let mut strings = Vec::new();
+    let mut start = 0;
+    for (index, chunk) in buffer.chunks(char_size).enumerate() {
+        if chunk.len() == char_size && chunk.iter().all(|b| *b == 0x00) {
+            strings.push(
+                decode_string(&buffer[start..index * char_size], unicode)?
+            );
+            start = (index + 1) * char_size;
+        }
+    }
+
+    Ok(strings)
+}
+
+// Items within zip archives browsed through Explorer.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#313-compressed-folder-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct CompressedFolderShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>
+}
+impl CompressedFolderShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, flags: u8, size: u16) -> Result<CompressedFolderShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let unicode = flags & HAS_UNICODE_STRINGS > 0;
+        let date_string_size = if unicode { DATE_STRING_CHARS * 2 } else { DATE_STRING_CHARS };
+
+        // subtract 4 from size to account for size(2), class_type(1), and flags(1)
+        let mut buffer = vec![0; size.saturating_sub(4) as usize];
+        reader.read_exact(&mut buffer)?;
+
+        let mut modified = None;
+        let mut accessed = None;
+        let mut path = None;
+        let mut parent = None;
+
+        // the buffer starts 4 bytes into the item, everything before the
+        // first date string is unknown
+        let date_start = DATE_STRING_OFFSET - 4;
+        if buffer.len() >= date_start + date_string_size {
+            modified = Some(
+                decode_string(&buffer[date_start..date_start + date_string_size], unicode)?
+            );
+        }
+
+        let date_start = date_start + date_string_size;
+        if buffer.len() >= date_start + date_string_size {
+            accessed = Some(
+                decode_string(&buffer[date_start..date_start + date_string_size], unicode)?
+            );
+
+            // The inner path and parent strings follow the date strings
+            let strings = split_strings(&buffer[date_start + date_string_size..], unicode)?;
+            path = strings.get(0).cloned();
+            parent = strings.get(1).cloned();
+        }
+
+        Ok(
+            CompressedFolderShellItem {
+                _offset: _offset,
+                modified: modified,
+                accessed: accessed,
+                path: path,
+                parent: parent
+            }
+        )
+    }
+}
+
+#[test]
+fn test_compressed_folder_item() {
+    use std::io::Cursor;
+    // Item data following the unknown byte: 32 unknown bytes, the modified and
+    // accessed date strings, then the path and parent strings
+    let buffer: &[u8] = &[
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x30,0x00,0x31,0x00,0x2F,0x00,0x31,0x00,0x35,0x00,0x2F,0x00,0x32,0x00,0x30,0x00,
+        0x31,0x00,0x35,0x00,0x20,0x00,0x20,0x00,0x31,0x00,0x31,0x00,0x3A,0x00,0x30,0x00,
+        0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x30,0x00,0x31,0x00,0x2F,0x00,0x31,0x00,
+        0x36,0x00,0x2F,0x00,0x32,0x00,0x30,0x00,0x31,0x00,0x35,0x00,0x20,0x00,0x20,0x00,
+        0x30,0x00,0x39,0x00,0x3A,0x00,0x33,0x00,0x30,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x64,0x00,0x6F,0x00,0x63,0x00,0x73,0x00,0x5C,0x00,0x61,0x00,0x2E,0x00,0x74,0x00,
+        0x78,0x00,0x74,0x00,0x00,0x00,0x74,0x00,0x65,0x00,0x73,0x00,0x74,0x00,0x2E,0x00,
+        0x7A,0x00,0x69,0x00,0x70,0x00,0x00,0x00
+    ];
+
+    let compressed_item = CompressedFolderShellItem::new(
+        Cursor::new(buffer), 0x80, 156
+    ).unwrap();
+    assert_eq!(compressed_item.modified,Some(String::from("01/15/2015  11:01")));
+    assert_eq!(compressed_item.accessed,Some(String::from("01/16/2015  09:30")));
+    assert_eq!(compressed_item.path,Some(String::from("docs\\a.txt")));
+    assert_eq!(compressed_item.parent,Some(String::from("test.zip")));
+}
+
+#[test]
+fn test_compressed_folder_item_ascii() {
+    use std::io::Cursor;
+    // The same item with ascii strings
+    let buffer: &[u8] = &[
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x30,0x31,0x2F,0x31,0x35,0x2F,0x32,0x30,0x31,0x35,0x20,0x20,0x31,0x31,0x3A,0x30,
+        0x31,0x00,0x00,0x00,0x30,0x31,0x2F,0x31,0x36,0x2F,0x32,0x30,0x31,0x35,0x20,0x20,
+        0x30,0x39,0x3A,0x33,0x30,0x00,0x00,0x00,0x64,0x6F,0x63,0x73,0x5C,0x61,0x2E,0x74,
+        0x78,0x74,0x00,0x74,0x65,0x73,0x74,0x2E,0x7A,0x69,0x70,0x00
+    ];
+
+    let compressed_item = CompressedFolderShellItem::new(
+        Cursor::new(buffer), 0x00, 96
+    ).unwrap();
+    assert_eq!(compressed_item.modified,Some(String::from("01/15/2015  11:01")));
+    assert_eq!(compressed_item.accessed,Some(String::from("01/16/2015  09:30")));
+    assert_eq!(compressed_item.path,Some(String::from("docs\\a.txt")));
+    assert_eq!(compressed_item.parent,Some(String::from("test.zip")));
+}
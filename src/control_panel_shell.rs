@@ -0,0 +1,192 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use errors::{ShellItemError};
+use guid::{Guid};
+use extension_blocks::{ExtensionList};
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::fmt;
+use serde::{ser};
+
+pub const CONTROL_PANEL_CATEGORY_SIGNATURE: u32 = 0x39DE2184;
+
+// Returns the display name of a well known control panel item identifier
+pub fn get_control_panel_item_name(guid: &Guid) -> Option<&'static str> {
+    match format!("{}", guid).as_str() {
+        "7B81BE6A-CE2B-4676-A29E-EB907A5126C5" => Some("Programs and Features"),
+        "BB06C0E4-D293-4F75-8A90-CB05B6477EEE" => Some("System"),
+        "4026492F-2F69-46B8-B9BF-5654FC07E423" => Some("Windows Firewall"),
+        "8E908FC9-BECC-40F6-915B-F4CA0E70D03D" => Some("Network and Sharing Center"),
+        "60632754-C523-4B62-B45C-4172DA012619" => Some("User Accounts"),
+        "36EEF7DB-88AD-4E81-AD49-0E313F0C35F8" => Some("Windows Update"),
+        "17CD9488-1228-4B2F-88CE-4298E93E0966" => Some("Default Programs"),
+        "025A5937-A6BE-4686-A844-36FE4BEC8B6D" => Some("Power Options"),
+        "74246BFC-4C96-11D0-ABEF-0020AF6B0B7A" => Some("Device Manager"),
+        "A8A91A66-3A7D-4424-8D24-04E180695C7A" => Some("Devices and Printers"),
+        "BB64F8A7-BEE7-4E1A-AB8D-7D8273F7FDB6" => Some("Action Center"),
+        "9C60DE1E-E5FC-40F4-A487-460851A8D915" => Some("AutoPlay"),
+        _ => None
+    }
+}
+
+#[derive(Clone)]
+pub struct ControlPanelCategory(
+    u32
+);
+impl ControlPanelCategory {
+    pub fn new(value: u32) -> ControlPanelCategory {
+        ControlPanelCategory(value)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self.0 {
+            0 => "All Control Panel Items",
+            1 => "Appearance and Personalization",
+            2 => "Hardware and Sound",
+            3 => "Network and Internet",
+            4 => "Sound, Speech and Audio Devices",
+            5 => "System and Security",
+            6 => "Clock, Language, and Region",
+            7 => "Ease of Access",
+            8 => "Programs",
+            9 => "User Accounts",
+            10 => "Security Center",
+            11 => "Mobile PC",
+            _ => "Unknown"
+        }
+    }
+}
+impl fmt::Display for ControlPanelCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.get_name())
+    }
+}
+impl fmt::Debug for ControlPanelCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{} ({})",self.get_name(),self.0)
+    }
+}
+impl ser::Serialize for ControlPanelCategory {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        serializer.serialize_str(
+            &format!("{}", self)
+        )
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#311-control-panel-category-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct ControlPanelCategoryShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    #[serde(skip_serializing)]
+    pub signature: u32,
+    pub category: ControlPanelCategory,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl ControlPanelCategoryShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<ControlPanelCategoryShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let signature = reader.read_u32::<LittleEndian>()?;
+        let category = ControlPanelCategory::new(
+            reader.read_u32::<LittleEndian>()?
+        );
+
+        // size(2), class_type(1), unknown(1), signature(4) and category(4) make up 12 bytes
+        let mut extension_blocks = None;
+        if size > 12 {
+            let mut extension_list = ExtensionList::new(&mut reader, size as u64 - 12)?;
+            extension_list.check_version_offsets(12);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            ControlPanelCategoryShellItem {
+                _offset: _offset,
+                signature: signature,
+                category: category,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#310-control-panel-item-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct ControlPanelShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl ControlPanelShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<ControlPanelShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        // 10 bytes of unknown, mostly empty, values
+        let mut _unknown = [0; 10];
+        reader.read_exact(&mut _unknown)?;
+        let guid = Guid::new(&mut reader)?;
+        let name = get_control_panel_item_name(&guid);
+
+        // size(2), class_type(1), unknown(1), unknown(10) and guid(16) make up 30 bytes
+        let mut extension_blocks = None;
+        if size > 30 {
+            let mut extension_list = ExtensionList::new(&mut reader, size as u64 - 30)?;
+            extension_list.check_version_offsets(30);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            ControlPanelShellItem {
+                _offset: _offset,
+                guid: guid,
+                name: name,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+#[test]
+fn test_control_panel_category_item() {
+    use std::io::Cursor;
+    let buffer: &[u8] = &[
+        0x84,0x21,0xDE,0x39,0x05,0x00,0x00,0x00
+    ];
+
+    let category_item = ControlPanelCategoryShellItem::new(
+        Cursor::new(buffer), 12
+    ).unwrap();
+    assert_eq!(category_item.signature,CONTROL_PANEL_CATEGORY_SIGNATURE);
+    assert_eq!(category_item.category.get_name(),"System and Security");
+}
+
+#[test]
+fn test_control_panel_item() {
+    use std::io::Cursor;
+    // The System control panel item followed by an extension block of an
+    // unknown signature
+    let buffer: &[u8] = &[
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0xE4,0xC0,0x06,0xBB,0x93,0xD2,
+        0x75,0x4F,0x8A,0x90,0xCB,0x05,0xB6,0x47,0x7E,0xEE,0x0A,0x00,0x00,0x00,0xFE,0x00,
+        0xEF,0xBE,0x1E,0x00
+    ];
+
+    let control_panel_item = ControlPanelShellItem::new(
+        Cursor::new(buffer), 40
+    ).unwrap();
+    assert_eq!(format!("{}",control_panel_item.guid),"BB06C0E4-D293-4F75-8A90-CB05B6477EEE");
+    assert_eq!(control_panel_item.name,Some("System"));
+    let extension_blocks = control_panel_item.extension_blocks.unwrap();
+    assert_eq!(extension_blocks.len(),1);
+    assert_eq!(extension_blocks[0].get_offset(),26);
+}
@@ -0,0 +1,263 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use errors::{ShellItemError};
+use guid::{Guid};
+use root_folder_shell;
+use extension_blocks::{ExtensionList};
+use shellitem::{ShellItem, RawContent};
+use property_store::{PropertyStore};
+use utils::{ReadSeek};
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::fmt;
+use std::cmp;
+use serde::{ser};
+
+// "CFSF"
+pub const DELEGATE_SIGNATURE: u32 = 0x46534643;
+pub const USERS_PROPERTY_VIEW_SIGNATURE: u32 = 0x23FEBBEE;
+
+#[derive(Clone)]
+pub struct ItemSignature(
+    u32
+);
+impl ItemSignature {
+    pub fn new(value: u32) -> ItemSignature {
+        ItemSignature(value)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+impl fmt::Display for ItemSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"0x{:08X}",self.0)
+    }
+}
+impl fmt::Debug for ItemSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"0x{:08X}",self.0)
+    }
+}
+impl ser::Serialize for ItemSignature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        serializer.serialize_str(
+            &format!("{}", self)
+        )
+    }
+}
+
+// Wraps a file entry shell item with the delegate and item class identifiers.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#35-delegate-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct DelegateShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub data_size: u16,
+    pub signature: ItemSignature,
+    pub inner_item: Box<ShellItem>,
+    pub delegate_guid: Guid,
+    pub item_guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl DelegateShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<DelegateShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let data_size = reader.read_u16::<LittleEndian>()?;
+        let signature = ItemSignature(reader.read_u32::<LittleEndian>()?);
+
+        // The inner item is stored as a shell item including its own size field
+        let inner_item = ShellItem::new(&mut reader as &mut dyn ReadSeek)?;
+        // a null inner item still occupies its 2 byte size field
+        let inner_size = cmp::max(inner_item.get_size() as u64, 2);
+        reader.seek(SeekFrom::Start(_offset + 6 + inner_size))?;
+
+        let delegate_guid = Guid::new(&mut reader)?;
+        let item_guid = Guid::new(&mut reader)?;
+        let item_name = root_folder_shell::get_shell_folder_name(&item_guid);
+
+        // size(2), class_type(1), unknown(1), data_size(2), signature(4)
+        // the inner item and both guids(32) precede any extension blocks
+        let mut extension_blocks = None;
+        let consumed = 10 + inner_size + 32;
+        if size as u64 >= consumed + 8 {
+            let mut extension_list = ExtensionList::new(&mut reader, size as u64 - consumed)?;
+            extension_list.check_version_offsets(consumed);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            DelegateShellItem {
+                _offset: _offset,
+                data_size: data_size,
+                signature: signature,
+                inner_item: Box::new(inner_item),
+                delegate_guid: delegate_guid,
+                item_guid: item_guid,
+                item_name: item_name,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+// Variable sized items (class type 0x00) identified by the signature at offset 6.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#312-users-property-view-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct UsersPropertyViewShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub data_size: u16,
+    pub signature: ItemSignature,
+    pub property_store_size: u16,
+    pub identifier_size: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<Guid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier_name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_identifier: Option<RawContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property_store: Option<PropertyStore>,
+    // The property store data if it could not be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_property_store: Option<RawContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl UsersPropertyViewShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<UsersPropertyViewShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let data_size = reader.read_u16::<LittleEndian>()?;
+        let signature = ItemSignature(reader.read_u32::<LittleEndian>()?);
+        let property_store_size = reader.read_u16::<LittleEndian>()?;
+        let identifier_size = reader.read_u16::<LittleEndian>()?;
+
+        let mut identifier = None;
+        let mut identifier_name = None;
+        let mut raw_identifier = None;
+        if identifier_size == 16 {
+            let guid = Guid::new(&mut reader)?;
+            identifier_name = root_folder_shell::get_shell_folder_name(&guid);
+            identifier = Some(guid);
+        } else if identifier_size > 0 {
+            let mut buffer = vec![0; identifier_size as usize];
+            reader.read_exact(&mut buffer)?;
+            raw_identifier = Some(RawContent(buffer));
+        }
+
+        let mut property_store = None;
+        let mut raw_property_store = None;
+        if property_store_size > 0 {
+            let mut buffer = vec![0; property_store_size as usize];
+            reader.read_exact(&mut buffer)?;
+            match PropertyStore::from_buffer(&buffer) {
+                Ok(store) => property_store = Some(store),
+                Err(error) => {
+                    warn!(
+                        "Unable to parse the property store of the item at offset {}: {}",
+                        _offset, error.message
+                    );
+                    raw_property_store = Some(RawContent(buffer));
+                }
+            }
+        }
+
+        // size(2), class_type(1), unknown(1), data_size(2), signature(4),
+        // property_store_size(2) and identifier_size(2) precede the identifier
+        // and property store
+        let mut extension_blocks = None;
+        let consumed = 14 + identifier_size as u64 + property_store_size as u64;
+        if size as u64 >= consumed + 8 {
+            let mut extension_list = ExtensionList::new(&mut reader, size as u64 - consumed)?;
+            extension_list.check_version_offsets(consumed);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            UsersPropertyViewShellItem {
+                _offset: _offset,
+                data_size: data_size,
+                signature: signature,
+                property_store_size: property_store_size,
+                identifier_size: identifier_size,
+                identifier: identifier,
+                identifier_name: identifier_name,
+                raw_identifier: raw_identifier,
+                property_store: property_store,
+                raw_property_store: raw_property_store,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+#[test]
+fn test_delegate_item() {
+    use shellitem::{ShellContent};
+    use std::io::Cursor;
+    // Delegate item wrapping a file entry named "A"
+    let buffer: &[u8] = &[
+        0x3A,0x00,0x74,0x00,0x10,0x00,0x43,0x46,0x53,0x46,0x10,0x00,0x31,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x41,0x00,0x74,0x1A,0x59,0x5E,0x96,0xDF,
+        0xD3,0x48,0x8D,0x67,0x17,0x33,0xBC,0xEE,0x28,0xBA,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,
+        0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D
+    ];
+
+    let shell_item = ShellItem::new(Cursor::new(buffer)).unwrap();
+    match shell_item.data.unwrap().content {
+        ShellContent::Delegate(ref delegate) => {
+            assert_eq!(delegate.signature.as_u32(),DELEGATE_SIGNATURE);
+            assert_eq!(delegate.inner_item.get_size(),16);
+            assert_eq!(delegate.item_name,Some("My Computer"));
+        },
+        _ => panic!("expected a delegate item")
+    }
+}
+
+#[test]
+fn test_users_property_view_extension_blocks() {
+    use shellitem::{ShellContent};
+    use std::io::Cursor;
+    // Users property view item with a My Computer identifier followed by an
+    // extension block of an unknown signature
+    let buffer: &[u8] = &[
+        0x28,0x00,0x00,0x00,0x22,0x00,0xEE,0xBB,0xFE,0x23,0x00,0x00,0x10,0x00,0xE0,0x4F,
+        0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x0A,0x00,
+        0x00,0x00,0xFE,0x00,0xEF,0xBE,0x0E,0x00
+    ];
+
+    let shell_item = ShellItem::new(Cursor::new(buffer)).unwrap();
+    match shell_item.data.unwrap().content {
+        ShellContent::UsersPropertyView(ref item) => {
+            assert_eq!(item.identifier_name,Some("My Computer"));
+            assert_eq!(item.extension_blocks.as_ref().unwrap().len(),1);
+        },
+        _ => panic!("expected a users property view item")
+    }
+}
+
+#[test]
+fn test_users_property_view_invalid_property_store() {
+    use shellitem::{ShellContent};
+    use std::io::Cursor;
+    // Users property view item with a bad property storage version
+    let buffer: &[u8] = &[
+        0x16,0x00,0x00,0x00,0x0E,0x00,0xEE,0xBB,0xFE,0x23,0x08,0x00,0x00,0x00,0x08,0x00,
+        0x00,0x00,0x41,0x41,0x41,0x41
+    ];
+
+    let shell_item = ShellItem::new(Cursor::new(buffer)).unwrap();
+    match shell_item.data.unwrap().content {
+        ShellContent::UsersPropertyView(ref item) => {
+            assert!(item.property_store.is_none());
+            assert_eq!(item.raw_property_store.as_ref().unwrap().0.len(),8);
+        },
+        _ => panic!("expected a users property view item")
+    }
+}
@@ -3,19 +3,49 @@ use std::fmt;
 use std::fmt::Display;
 use std::io;
 
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub enum ErrorKind {
     IoError,
-    Utf16Error
+    Utf16Error,
+    InvalidSignature,
+    UnsupportedVersion,
+    MaxDepthExceeded
+}
+
+// Structural problems found while parsing that do not prevent parsing
+#[derive(Serialize, Clone, Debug)]
+pub enum Anomaly {
+    // The version offset of an extension block does not point back to the
+    // start of the block relative to its shell item
+    VersionOffsetMismatch {
+        expected: u64,
+        found: u16
+    },
+    // Bytes at the end of a structure that were not consumed by its parser
+    LeftoverBytes {
+        offset: u64,
+        size: u64
+    },
+    // The parser of a structure tried to read past the end of its declared size
+    Overrun {
+        offset: u64,
+        size: u64
+    },
+    // The content at `offset` could not be parsed and was kept as raw data
+    ParseError {
+        offset: u64,
+        message: String
+    }
 }
 
 // Lnk Parsing Error
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug)]
 pub struct ShellItemError {
     // Formated error message
     pub message: String,
     // The type of error
     pub kind: ErrorKind,
+    #[serde(skip_serializing)]
     pub trace: String
 }
 impl ShellItemError {
@@ -27,6 +57,33 @@ impl ShellItemError {
             trace: backtrace!()
         }
     }
+
+    #[allow(dead_code)]
+    pub fn invalid_signature(err: String)->Self{
+        ShellItemError {
+            message: format!("{}",err),
+            kind: ErrorKind::InvalidSignature,
+            trace: backtrace!()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn unsupported_version(err: String)->Self{
+        ShellItemError {
+            message: format!("{}",err),
+            kind: ErrorKind::UnsupportedVersion,
+            trace: backtrace!()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn max_depth_exceeded(err: String)->Self{
+        ShellItemError {
+            message: format!("{}",err),
+            kind: ErrorKind::MaxDepthExceeded,
+            trace: backtrace!()
+        }
+    }
 }
 impl From<io::Error> for ShellItemError {
     fn from(err: io::Error) -> Self {
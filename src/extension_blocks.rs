@@ -1,14 +1,25 @@
 use byteorder::{ReadBytesExt, LittleEndian};
 use serde::{ser};
-use errors::{ShellItemError};
-use rwinstructs::timestamp::DosDateTime;
+use errors::{ShellItemError, ErrorKind, Anomaly};
+use property_store::{PropertyStore};
+use guid::{Guid};
+use shelllist::{ShellList, ParseMode};
+use registry;
+use serde_json::{Value};
+use rwinstructs::timestamp::{DosDateTime, WinTimestamp};
 use rwinstructs::reference::MftReference;
 use utils;
+use utils::{WindowReader};
 use std::io::Cursor;
 use std::io::Read;
 use std::io::{Seek,SeekFrom};
 use std::fmt;
+use std::cmp;
+use std::ops::Index;
+use std::slice;
+use std::vec;
 
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0004
 #[derive(Serialize, Clone, Debug)]
 pub struct Beef0004 {
     #[serde(skip_serializing)]
@@ -17,118 +28,713 @@ pub struct Beef0004 {
     last_access: DosDateTime,
     identifier: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
+    unknown1: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     file_reference: Option<MftReference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown2: Option<u64>,
     #[serde(skip_serializing)]
     long_string_size: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    name: Option<String>,
+    unknown3: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    long_name: Option<String>,
+    unknown4: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     localized_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    version_offset: Option<u16>
+    version_offset: Option<u16>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    anomalies: Vec<Anomaly>
 }
 impl Beef0004 {
     pub fn new<Rs: Read+Seek>(mut reader: Rs, extention_version: u16) -> Result<Beef0004, ShellItemError> {
         let _offset = reader.seek(SeekFrom::Current(0))?;
+
+        // Version 3 is used by XP, 7 by Vista, 8 by Windows 7 and 9 by Windows 8 and later.
+        // Each version only adds fields to the previous ones. The layouts of versions
+        // 4 to 6 are unknown.
+        match extention_version {
+            3 | 7 | 8 | 9 => {},
+            _ => return Err(
+                ShellItemError::unsupported_version(
+                    format!("Unhandled extention_version {} for Beef0004",extention_version)
+                )
+            )
+        }
+
         let creation = DosDateTime(reader.read_u32::<LittleEndian>()?);
         let last_access = DosDateTime(reader.read_u32::<LittleEndian>()?);
         let identifier = reader.read_u16::<LittleEndian>()?;
+        let mut unknown1 = None;
         let mut file_reference = None;
-        let mut long_string_size = None;
-        let mut name = None;
-        let mut long_name = None;
+        let mut unknown2 = None;
+        let mut unknown3 = None;
+        let mut unknown4 = None;
         let mut localized_name = None;
-        let mut version_offset = None;
 
-        match extention_version {
-            3 => {
-                long_string_size = Some(reader.read_u16::<LittleEndian>()?);
-                name = Some(
+        if extention_version >= 7 {
+            // Empty in all observed data
+            unknown1 = Some(reader.read_u16::<LittleEndian>()?);
+            file_reference = Some(
+                MftReference(reader.read_u64::<LittleEndian>()?)
+            );
+            unknown2 = Some(reader.read_u64::<LittleEndian>()?);
+        }
+
+        let long_string_size = Some(reader.read_u16::<LittleEndian>()?);
+
+        if extention_version >= 8 {
+            unknown3 = Some(reader.read_u32::<LittleEndian>()?);
+        }
+        if extention_version >= 9 {
+            unknown4 = Some(reader.read_u32::<LittleEndian>()?);
+        }
+
+        let name = Some(
+            utils::read_string_u16_till_null(&mut reader)?
+        );
+
+        if long_string_size.unwrap() > 0 {
+            if extention_version >= 7 {
+                localized_name = Some(
                     utils::read_string_u16_till_null(&mut reader)?
                 );
-                if long_string_size.unwrap() > 0 {
-                    long_name = Some(
-                        utils::read_string_u16_till_null(&mut reader)?
-                    );
+            } else {
+                let ascii_name = utils::read_string_u8_till_null(&mut reader)?;
+                // Add 1 to name length to account for null byte
+                if (ascii_name.len() + 1) % 2 > 0 {
+                    // throw away align byte
+                    reader.read_u8()?;
                 }
+                localized_name = Some(ascii_name);
+            }
+        }
+
+        let version_offset = Some(
+            reader.read_u16::<LittleEndian>()?
+        );
 
-                version_offset = Some(
-                    reader.read_u16::<LittleEndian>()?
+        Ok(
+            Beef0004 {
+                _offset: _offset,
+                creation: creation,
+                last_access: last_access,
+                identifier: identifier,
+                unknown1: unknown1,
+                file_reference: file_reference,
+                unknown2: unknown2,
+                long_string_size: long_string_size,
+                unknown3: unknown3,
+                unknown4: unknown4,
+                name: name,
+                localized_name: localized_name,
+                version_offset: version_offset,
+                anomalies: Vec::new()
+            }
+        )
+    }
+
+    // The version offset is the offset of the extension block relative to the
+    // start of the shell item it belongs to. A mismatch points to a tampered or
+    // hand-crafted item.
+    pub fn check_version_offset(&mut self, block_offset: u64) {
+        if let Some(found) = self.version_offset {
+            if found as u64 != block_offset {
+                warn!(
+                    "Beef0004 at offset {} has version_offset {} but block is at item offset {}",
+                    self._offset, found, block_offset
                 );
-            },
-            8 => {
-                let _unknown1 = reader.read_u16::<LittleEndian>()?;
-                file_reference = Some(
-                    MftReference(reader.read_u64::<LittleEndian>()?)
+                self.anomalies.push(
+                    Anomaly::VersionOffsetMismatch {
+                        expected: block_offset,
+                        found: found
+                    }
                 );
-                let _unknown2 = reader.read_u64::<LittleEndian>()?;
-                long_string_size = Some(reader.read_u16::<LittleEndian>()?);
-                let _unknown3 = reader.read_u32::<LittleEndian>()?;
+            }
+        }
+    }
 
-                name = Some(
-                    utils::read_string_u16_till_null(&mut reader)?
-                );
+    pub fn get_anomalies(&self) -> &Vec<Anomaly> {
+        &self.anomalies
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self._offset
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| name.as_str())
+    }
+}
+
+// Localized name of the item, e.g. "@shell32.dll,-21798"
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0016
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0016 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    localized_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0016 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0016, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 0)?;
+        let localized_name = utils::decode_utf16le(&data)?;
+
+        Ok(
+            Beef0016 {
+                _offset: _offset,
+                localized_name: localized_name,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Found on search and library items. Only the leading flags are understood,
+// the values that follow are kept as unknown.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0017
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0017 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    flags: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<RawExtensionContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0017 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0017, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 4)?;
+        let flags = (&data[..]).read_u32::<LittleEndian>()?;
+
+        let mut unknown = None;
+        if data.len() > 4 {
+            unknown = Some(
+                RawExtensionContent(data[4..].to_vec())
+            );
+        }
+
+        Ok(
+            Beef0017 {
+                _offset: _offset,
+                flags: flags,
+                unknown: unknown,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Found on MTP (portable device) items. The leading value and the identifier of
+// the device object are named, the values that follow are kept as unknown.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0029
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0029 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    unknown1: u32,
+    guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<RawExtensionContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0029 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0029, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 20)?;
+        let mut data_reader = Cursor::new(&data[..]);
+        let unknown1 = data_reader.read_u32::<LittleEndian>()?;
+        let guid = Guid::new(&mut data_reader)?;
+
+        let mut unknown = None;
+        if data.len() > 20 {
+            unknown = Some(
+                RawExtensionContent(data[20..].to_vec())
+            );
+        }
+
+        Ok(
+            Beef0029 {
+                _offset: _offset,
+                unknown1: unknown1,
+                guid: guid,
+                unknown: unknown,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Two timestamps of unknown meaning preceded by flags, found on newer Windows builds.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0025
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0025 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    flags: u32,
+    filetime1: WinTimestamp,
+    filetime2: WinTimestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0025 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0025, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let flags = reader.read_u32::<LittleEndian>()?;
+        let filetime1 = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+        let filetime2 = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+
+        // header(8), flags(4) and the timestamps(16) make up 28 bytes
+        let mut version_offset = None;
+        if size >= 30 {
+            version_offset = Some(
+                reader.read_u16::<LittleEndian>()?
+            );
+        }
+
+        Ok(
+            Beef0025 {
+                _offset: _offset,
+                flags: flags,
+                filetime1: filetime1,
+                filetime2: filetime2,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// High resolution timestamps of the item, found on Windows 7 and later.
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0026
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0026 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    unknown1: u32,
+    creation: WinTimestamp,
+    modification: WinTimestamp,
+    last_access: WinTimestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0026 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0026, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let unknown1 = reader.read_u32::<LittleEndian>()?;
+        let creation = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+        let modification = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+        let last_access = WinTimestamp(reader.read_u64::<LittleEndian>()?);
+
+        // header(8), unknown1(4) and the timestamps(24) make up 36 bytes
+        let mut version_offset = None;
+        if size >= 38 {
+            version_offset = Some(
+                reader.read_u16::<LittleEndian>()?
+            );
+        }
+
+        Ok(
+            Beef0026 {
+                _offset: _offset,
+                unknown1: unknown1,
+                creation: creation,
+                modification: modification,
+                last_access: last_access,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Reads the block data following the header(8), returning the data and the
+// trailing version offset. As with Beef0025 and Beef0026 the version offset is
+// only present when the data has room for it after the `fixed_size` bytes of
+// fields the block always has.
+fn read_block_data<R: Read>(mut reader: R, size: u16, fixed_size: usize) -> Result<(Vec<u8>, Option<u16>), ShellItemError> {
+    let mut buffer = vec![0; size.saturating_sub(8) as usize];
+    reader.read_exact(&mut buffer)?;
+
+    let mut version_offset = None;
+    if buffer.len() >= fixed_size + 2 {
+        let data_size = buffer.len() - 2;
+        version_offset = Some(
+            (&buffer[data_size..]).read_u16::<LittleEndian>()?
+        );
+        buffer.truncate(data_size);
+    }
+
+    Ok((buffer, version_offset))
+}
+
+// Contains a shell item list, e.g. for search results
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef000a
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef000A {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    shell_items: ShellList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef000A {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef000A, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let mut data_reader = Cursor::new(&data[..]);
+        let guid = Guid::new(&mut data_reader)?;
+        let shell_items = ShellList::new_with_mode(&mut data_reader, ParseMode::Lenient)?;
+
+        Ok(
+            Beef000A {
+                _offset: _offset,
+                guid: guid,
+                shell_items: shell_items,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Contains a shell item list, e.g. for library locations
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef000c
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef000C {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    shell_items: ShellList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef000C {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef000C, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 0)?;
+        let shell_items = ShellList::new_with_mode(Cursor::new(&data[..]), ParseMode::Lenient)?;
+
+        Ok(
+            Beef000C {
+                _offset: _offset,
+                shell_items: shell_items,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Contains a shell item list followed by a property store, e.g. for Explorer views
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef000e
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef000E {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    shell_items: ShellList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    property_store: Option<PropertyStore>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef000E {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef000E, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let mut data_reader = Cursor::new(&data[..]);
+        let guid = Guid::new(&mut data_reader)?;
+        let shell_items = ShellList::new_with_mode(&mut data_reader, ParseMode::Lenient)?;
+
+        let remaining = &data[data_reader.position() as usize..];
+        let mut property_store = None;
+        if let Some(store_start) = find_property_store(remaining) {
+            property_store = Some(
+                PropertyStore::from_buffer(&remaining[store_start..])?
+            );
+        }
+
+        Ok(
+            Beef000E {
+                _offset: _offset,
+                guid: guid,
+                shell_items: shell_items,
+                property_store: property_store,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Shell folder identifier of the item
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0003
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0003 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0003 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0003, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let guid = Guid::new(&data[..])?;
+
+        Ok(
+            Beef0003 {
+                _offset: _offset,
+                guid: guid,
+                version_offset: version_offset
+            }
+        )
+    }
+}
 
-                if long_string_size.unwrap() > 0 {
-                    long_name = Some(
-                        utils::read_string_u16_till_null(&mut reader)?
-                    );
+// User/profile information made up of an identifier and a shell item list
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0005
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0005 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    shell_items: ShellList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0005 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0005, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let mut data_reader = Cursor::new(&data[..]);
+        let guid = Guid::new(&mut data_reader)?;
+        let shell_items = ShellList::new_with_mode(&mut data_reader, ParseMode::Lenient)?;
+
+        Ok(
+            Beef0005 {
+                _offset: _offset,
+                guid: guid,
+                shell_items: shell_items,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+// Name of the user account the item belongs to
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0006
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0006 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    user_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0006 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0006, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 0)?;
+        let user_name = utils::decode_utf16le(&data)?;
+
+        Ok(
+            Beef0006 {
+                _offset: _offset,
+                user_name: user_name,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UriProperty {
+    identifier: u32,
+    value: String
+}
+
+// Properties of the uri in uri shell items
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0014
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0014 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    properties: Vec<UriProperty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0014 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0014, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let mut data_reader = Cursor::new(data);
+        let guid = Guid::new(&mut data_reader)?;
+
+        // Each property is an identifier(4) and size(4) followed by a utf16 string
+        let mut properties: Vec<UriProperty> = Vec::new();
+        loop {
+            let remaining = data_reader.get_ref().len() as u64 - data_reader.position();
+            if remaining < 8 {
+                break
+            }
+
+            let identifier = data_reader.read_u32::<LittleEndian>()?;
+            let value_size = data_reader.read_u32::<LittleEndian>()? as u64;
+            if (identifier == 0 && value_size == 0) || value_size > remaining - 8 {
+                break
+            }
+
+            let mut buffer = vec![0; value_size as usize];
+            data_reader.read_exact(&mut buffer)?;
+            properties.push(
+                UriProperty {
+                    identifier: identifier,
+                    value: utils::decode_utf16le(&buffer)?
                 }
+            );
+        }
+
+        Ok(
+            Beef0014 {
+                _offset: _offset,
+                guid: guid,
+                properties: properties,
+                version_offset: version_offset
+            }
+        )
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0013
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0013 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<RawExtensionContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0013 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0013, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 16)?;
+        let guid = Guid::new(&data[..])?;
 
-                version_offset = Some(
-                    reader.read_u16::<LittleEndian>()?
-                );
-            },
-            9 => {
-                let _unknown1 = reader.read_u16::<LittleEndian>()?;
-                file_reference = Some(
-                    MftReference(reader.read_u64::<LittleEndian>()?)
-                );
-                let _unknown2 = reader.read_u64::<LittleEndian>()?;
-                long_string_size = Some(reader.read_u16::<LittleEndian>()?);
-                let _unknown3 = reader.read_u32::<LittleEndian>()?;
-                let _unknown4 = reader.read_u32::<LittleEndian>()?;
+        let mut unknown = None;
+        if data.len() > 16 {
+            unknown = Some(
+                RawExtensionContent(data[16..].to_vec())
+            );
+        }
 
-                name = Some(
-                    utils::read_string_u16_till_null(&mut reader)?
-                );
+        Ok(
+            Beef0013 {
+                _offset: _offset,
+                guid: guid,
+                unknown: unknown,
+                version_offset: version_offset
+            }
+        )
+    }
+}
 
-                if long_string_size.unwrap() > 0 {
-                    long_name = Some(
-                        utils::read_string_u16_till_null(&mut reader)?
-                    );
-                }
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#extension_block_0xbeef0019
+#[derive(Serialize, Clone, Debug)]
+pub struct Beef0019 {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    guid1: Guid,
+    guid2: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<RawExtensionContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_offset: Option<u16>
+}
+impl Beef0019 {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<Beef0019, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let (data, version_offset) = read_block_data(&mut reader, size, 32)?;
+        let mut data_reader = Cursor::new(&data[..]);
+        let guid1 = Guid::new(&mut data_reader)?;
+        let guid2 = Guid::new(&mut data_reader)?;
 
-                version_offset = Some(
-                    reader.read_u16::<LittleEndian>()?
-                );
-            },
-            _ => {
-                panic!(
-                    format!("Unhandled extention_version {} for Beef0004",extention_version)
-                );
-            }
+        let mut unknown = None;
+        if data.len() > 32 {
+            unknown = Some(
+                RawExtensionContent(data[32..].to_vec())
+            );
         }
 
         Ok(
-            Beef0004 {
+            Beef0019 {
                 _offset: _offset,
-                creation: creation,
-                last_access: last_access,
-                identifier: identifier,
-                file_reference: file_reference,
-                long_string_size: long_string_size,
-                name: name,
-                long_name: long_name,
-                localized_name: localized_name,
+                guid1: guid1,
+                guid2: guid2,
+                unknown: unknown,
                 version_offset: version_offset
             }
         )
     }
 }
 
+// Returns the offset of the first serialized property store within the buffer
+fn find_property_store(buffer: &[u8]) -> Option<usize> {
+    // the store starts with its size(4) followed by the "1SPS" version
+    buffer.windows(4)
+        .skip(4)
+        .position(|w| w == b"1SPS")
+}
+
+// Extension blocks that carry a serialized property store. Any bytes preceding
+// the store within the block are kept as unknown.
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyStoreExtension {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<RawExtensionContent>,
+    property_store: PropertyStore
+}
+impl PropertyStoreExtension {
+    // Returns None if the buffer does not contain a property store
+    pub fn new(buffer: &[u8], _offset: u64) -> Result<Option<PropertyStoreExtension>, ShellItemError> {
+        let store_start = match find_property_store(buffer) {
+            Some(position) => position,
+            None => return Ok(None)
+        };
+
+        let mut unknown = None;
+        if store_start > 0 {
+            unknown = Some(
+                RawExtensionContent(buffer[..store_start].to_vec())
+            );
+        }
+
+        let property_store = PropertyStore::from_buffer(
+            &buffer[store_start..]
+        )?;
+
+        Ok(
+            Some(
+                PropertyStoreExtension {
+                    _offset: _offset,
+                    unknown: unknown,
+                    property_store: property_store
+                }
+            )
+        )
+    }
+}
+
 // Raw Content will be used for unhandled shell item data
 #[derive(Clone)]
 pub struct RawExtensionContent(
@@ -186,8 +792,25 @@ impl ser::Serialize for ExtensionSignature {
 #[derive(Serialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum ExtensionContent {
+    Beef0003(Beef0003),
     FileEntry(Beef0004),
+    Beef0005(Beef0005),
+    Beef0006(Beef0006),
+    Beef000A(Beef000A),
+    Beef000C(Beef000C),
+    Beef000E(Beef000E),
+    Beef0013(Beef0013),
+    Beef0014(Beef0014),
+    Beef0016(Beef0016),
+    Beef0017(Beef0017),
+    Beef0019(Beef0019),
+    Beef0025(Beef0025),
+    Beef0026(Beef0026),
+    Beef0029(Beef0029),
+    PropertyStore(PropertyStoreExtension),
     Raw(RawExtensionContent),
+    // Content produced by a parser registered with registry::register_extension_parser
+    Custom(Value),
     None
 }
 
@@ -229,67 +852,278 @@ pub struct ExtensionBlock {
     #[serde(skip_serializing)]
     size: u16,
     header: Option<ExtensionHeader>,
-    content: Option<ExtensionContent>
+    content: Option<ExtensionContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    anomalies: Vec<Anomaly>
 }
 impl ExtensionBlock {
+    // The block is parsed from a window limited to its size so a malformed block
+    // can not read into the data that follows it. The reader is always left at
+    // the end of the block.
     pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<ExtensionBlock, ShellItemError> {
         let _offset = reader.seek(SeekFrom::Current(0))?;
         let size = reader.read_u16::<LittleEndian>()?;
 
         let mut header_opt = None;
         let mut content = None;
+        let mut anomalies = Vec::new();
 
         if size > 0 {
-            header_opt = Some(
-                ExtensionHeader::new(&mut reader)?
-            );
-        }
+            // subtract 2 from size to account for size(2)
+            let mut window = WindowReader::new(&mut reader, size.saturating_sub(2) as u64)?;
+            let header = ExtensionHeader::new(&mut window)?;
 
-        match header_opt {
-            Some(ref header) => {
-                // // subtarct size of 8 for header
-                // let mut buffer = vec![0; (size - 8) as usize];
-                // reader.read_exact(&mut buffer)?;
-
-                match header.get_signature_u32() {
-                    0xBEEF0004 => {
-                        content = Some(
-                            ExtensionContent::FileEntry(
-                                Beef0004::new(
-                                    // Cursor::new(buffer),
-                                    &mut reader,
-                                    header.get_version_u32()
-                                )?
-                            )
+            let content_offset = window.seek(SeekFrom::Current(0))?;
+            content = match ExtensionBlock::read_content(&mut window, &header, size) {
+                Ok(content) => content,
+                Err(error) => {
+                    // content that fails to parse is kept as raw data so one bad
+                    // block does not fail the item it belongs to
+                    if error.kind == ErrorKind::IoError && window.get_overrun() > 0 {
+                        warn!(
+                            "Extension block at offset {} reads {} bytes past its size of {}: {}",
+                            _offset, window.get_overrun(), size, error.message
                         );
-                    }
-                    _ => {
-                        let mut buffer = vec![0; (size - 8) as usize];
-                        reader.read_exact(&mut buffer)?;
-                        content = Some(
-                            ExtensionContent::Raw(
-                                RawExtensionContent(buffer)
-                            )
+                        anomalies.push(
+                            Anomaly::Overrun {
+                                offset: window.get_end(),
+                                size: window.get_overrun()
+                            }
+                        );
+                    } else {
+                        warn!(
+                            "Unable to parse extension block at offset {}: {}",
+                            _offset, error.message
+                        );
+                        anomalies.push(
+                            Anomaly::ParseError {
+                                offset: content_offset,
+                                message: error.message
+                            }
                         );
                     }
+
+                    window.seek(SeekFrom::Start(content_offset))?;
+                    let mut buffer = Vec::new();
+                    window.read_to_end(&mut buffer)?;
+                    Some(
+                        ExtensionContent::Raw(
+                            RawExtensionContent(buffer)
+                        )
+                    )
                 }
-            },
-            None => {}
+            };
+
+            let leftover = window.remaining()?;
+            if leftover > 0 {
+                anomalies.push(
+                    Anomaly::LeftoverBytes {
+                        offset: window.get_end() - leftover,
+                        size: leftover
+                    }
+                );
+            }
+
+            header_opt = Some(header);
         }
 
+        // a null block still consumes its size field
+        reader.seek(SeekFrom::Start(_offset + cmp::max(size, 2) as u64))?;
+
         Ok(
             ExtensionBlock {
                 _offset: _offset,
                 size: size,
                 header: header_opt,
-                content: content
+                content: content,
+                anomalies: anomalies
             }
         )
     }
 
+    fn read_content<Rs: Read+Seek>(mut reader: Rs, header: &ExtensionHeader, size: u16) -> Result<Option<ExtensionContent>, ShellItemError> {
+        // registered parsers take precedence over the built in ones
+        let custom = registry::parse_extension(
+            &mut reader, header.get_signature_u32(), header.get_version_u32(), size.saturating_sub(8) as usize
+        )?;
+        if let Some(value) = custom {
+            return Ok(
+                Some(ExtensionContent::Custom(value))
+            );
+        }
+
+        let content = match header.get_signature_u32() {
+            0xBEEF0003 => {
+                Some(
+                    ExtensionContent::Beef0003(
+                        Beef0003::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0004 => {
+                Some(
+                    ExtensionContent::FileEntry(
+                        Beef0004::new(&mut reader, header.get_version_u32())?
+                    )
+                )
+            },
+            0xBEEF0005 => {
+                Some(
+                    ExtensionContent::Beef0005(
+                        Beef0005::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0006 => {
+                Some(
+                    ExtensionContent::Beef0006(
+                        Beef0006::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF000A => {
+                Some(
+                    ExtensionContent::Beef000A(
+                        Beef000A::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF000C => {
+                Some(
+                    ExtensionContent::Beef000C(
+                        Beef000C::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF000E => {
+                Some(
+                    ExtensionContent::Beef000E(
+                        Beef000E::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0013 => {
+                Some(
+                    ExtensionContent::Beef0013(
+                        Beef0013::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0014 => {
+                Some(
+                    ExtensionContent::Beef0014(
+                        Beef0014::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0016 => {
+                Some(
+                    ExtensionContent::Beef0016(
+                        Beef0016::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0017 => {
+                Some(
+                    ExtensionContent::Beef0017(
+                        Beef0017::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0019 => {
+                Some(
+                    ExtensionContent::Beef0019(
+                        Beef0019::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0025 => {
+                Some(
+                    ExtensionContent::Beef0025(
+                        Beef0025::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0026 => {
+                Some(
+                    ExtensionContent::Beef0026(
+                        Beef0026::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0029 => {
+                Some(
+                    ExtensionContent::Beef0029(
+                        Beef0029::new(&mut reader, size)?
+                    )
+                )
+            },
+            0xBEEF0010 | 0xBEEF0021 | 0xBEEF0027 => {
+                let content_offset = reader.seek(SeekFrom::Current(0))?;
+                let mut buffer = vec![0; (size - 8) as usize];
+                reader.read_exact(&mut buffer)?;
+
+                match PropertyStoreExtension::new(&buffer, content_offset) {
+                    Ok(Some(property_store)) => Some(
+                        ExtensionContent::PropertyStore(property_store)
+                    ),
+                    _ => Some(
+                        ExtensionContent::Raw(
+                            RawExtensionContent(buffer)
+                        )
+                    )
+                }
+            },
+            _ => {
+                let mut buffer = vec![0; (size - 8) as usize];
+                reader.read_exact(&mut buffer)?;
+                Some(
+                    ExtensionContent::Raw(
+                        RawExtensionContent(buffer)
+                    )
+                )
+            }
+        };
+
+        Ok(content)
+    }
+
     pub fn get_size(&self) -> u16 {
         self.size
     }
+
+    pub fn get_offset(&self) -> u64 {
+        self._offset
+    }
+
+    // None for a null block
+    pub fn get_header(&self) -> Option<&ExtensionHeader> {
+        self.header.as_ref()
+    }
+
+    pub fn get_signature(&self) -> Option<u32> {
+        self.header.as_ref().map(|header| header.get_signature_u32())
+    }
+
+    pub fn get_version(&self) -> Option<u16> {
+        self.header.as_ref().map(|header| header.get_version_u32())
+    }
+
+    pub fn get_content(&self) -> Option<&ExtensionContent> {
+        self.content.as_ref()
+    }
+
+    pub fn get_anomalies(&self) -> &Vec<Anomaly> {
+        &self.anomalies
+    }
+
+    // Validates the version offset of blocks that carry one against the offset
+    // of this block relative to the start of its shell item
+    pub fn check_version_offset(&mut self, block_offset: u64) {
+        if let Some(ExtensionContent::FileEntry(ref mut beef0004)) = self.content {
+            beef0004.check_version_offset(block_offset);
+        }
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -298,28 +1132,659 @@ pub struct ExtensionList(
 );
 
 impl ExtensionList {
-    pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<ExtensionList, ShellItemError> {
+    // Reads extension blocks until `size` bytes are exhausted or a null block is
+    // found.
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, size: u64) -> Result<ExtensionList, ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let end = _offset + size;
         let mut extension_blocks: Vec<ExtensionBlock> = Vec::new();
         loop {
-            let extension_block = ExtensionBlock::new(
-                &mut reader
-            )?;
-            let size = extension_block.get_size();
+            let block_offset = reader.seek(SeekFrom::Current(0))?;
+            // size(2), version(2) and signature(4) is the smallest possible block
+            if block_offset + 8 > end {
+                break
+            }
 
-            if size == 0 {
-                // Null shell item is terminator
+            let block_size = reader.read_u16::<LittleEndian>()? as u64;
+            reader.seek(SeekFrom::Start(block_offset))?;
+            if block_size == 0 {
+                // Null block is terminator
+                break
+            }
+            if block_size < 8 || block_offset + block_size > end {
+                warn!(
+                    "Extension block at offset {} has an invalid size of {}",
+                    block_offset, block_size
+                );
                 break
             }
 
             extension_blocks.push(
-                extension_block
+                ExtensionBlock::new(&mut reader)?
             );
-
-            break
         }
 
         Ok(
             ExtensionList(extension_blocks)
         )
     }
+
+    pub fn items(&self) -> &[ExtensionBlock] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> slice::Iter<ExtensionBlock> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // The long name of the first Beef0004 block in the list
+    pub fn get_long_name(&self) -> Option<&str> {
+        for block in self.0.iter() {
+            if let Some(ExtensionContent::FileEntry(ref beef0004)) = block.content {
+                return beef0004.get_name();
+            }
+        }
+        None
+    }
+
+    // Validates the version offsets of the blocks where `list_offset` is the
+    // offset of the first block relative to the start of its shell item
+    pub fn check_version_offsets(&mut self, list_offset: u64) {
+        let list_start = match self.0.first() {
+            Some(block) => block._offset,
+            None => return
+        };
+
+        for block in self.0.iter_mut() {
+            let block_offset = block._offset - list_start + list_offset;
+            block.check_version_offset(block_offset);
+        }
+    }
+}
+impl Index<usize> for ExtensionList {
+    type Output = ExtensionBlock;
+
+    fn index(&self, index: usize) -> &ExtensionBlock {
+        &self.0[index]
+    }
+}
+impl IntoIterator for ExtensionList {
+    type Item = ExtensionBlock;
+    type IntoIter = vec::IntoIter<ExtensionBlock>;
+
+    fn into_iter(self) -> vec::IntoIter<ExtensionBlock> {
+        self.0.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a ExtensionList {
+    type Item = &'a ExtensionBlock;
+    type IntoIter = slice::Iter<'a, ExtensionBlock>;
+
+    fn into_iter(self) -> slice::Iter<'a, ExtensionBlock> {
+        self.0.iter()
+    }
+}
+
+#[test]
+fn test_beef0004_unsupported_version() {
+    let buffer: &[u8] = &[
+        0x0C,0x00,0x02,0x00,0x04,0x00,0xEF,0xBE,0x01,0x02,0x03,0x04
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Raw(ref raw)) => assert_eq!(raw.0.len(),4),
+        _ => panic!("expected raw content for an unsupported version")
+    }
+    match extension_block.get_anomalies().first() {
+        Some(&Anomaly::ParseError { offset, .. }) => assert_eq!(offset,8),
+        _ => panic!("expected a parse error anomaly")
+    }
+}
+
+#[test]
+fn test_beef0004_unknown_layout_version() {
+    // Versions between 3 and 7 have no known layout
+    let buffer: &[u8] = &[
+        0x0C,0x00,0x05,0x00,0x04,0x00,0xEF,0xBE,0x01,0x02,0x03,0x04
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Raw(ref raw)) => assert_eq!(raw.0.len(),4),
+        _ => panic!("expected raw content for version 5")
+    }
+    match extension_block.get_anomalies().first() {
+        Some(&Anomaly::ParseError { offset, .. }) => assert_eq!(offset,8),
+        _ => panic!("expected a parse error anomaly")
+    }
+}
+
+#[test]
+fn test_extension_block_overrun() {
+    // A version 3 Beef0004 block that is too small for its fields followed by
+    // data that does not belong to the block
+    let buffer: &[u8] = &[
+        0x0C,0x00,0x03,0x00,0x04,0x00,0xEF,0xBE,0x01,0x02,0x03,0x04,0xFF,0xFF
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),12);
+    match extension_block.get_anomalies().first() {
+        Some(&Anomaly::Overrun { offset, .. }) => assert_eq!(offset,12),
+        _ => panic!("expected an overrun anomaly")
+    }
+}
+
+#[test]
+fn test_extension_list() {
+    // A version 3 Beef0004 block named "A", a block of an unknown signature and
+    // a block past the declared size of the list
+    let buffer: &[u8] = &[
+        0x1A,0x00,0x03,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x14,0x00,0x00,0x00,0x41,0x00,0x00,0x00,0x14,0x00,0x0A,0x00,0x00,0x00,0xFE,0x00,
+        0xEF,0xBE,0x2E,0x00,0x0A,0x00,0x00,0x00,0xFD,0x00,0xEF,0xBE,0x38,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_list = ExtensionList::new(&mut reader, 36).unwrap();
+    assert_eq!(extension_list.len(),2);
+    assert_eq!(reader.position(),36);
+    match extension_list[0].get_content() {
+        Some(&ExtensionContent::FileEntry(ref beef0004)) => assert_eq!(beef0004.get_name(),Some("A")),
+        _ => panic!("expected a Beef0004 block")
+    }
+    assert_eq!(extension_list[1].get_signature(),Some(0xBEEF00FE));
+    assert_eq!(extension_list[1].get_version(),Some(0));
+    match extension_list[1].get_content() {
+        Some(&ExtensionContent::Raw(ref raw)) => assert_eq!(raw.0,vec![0x2E,0x00]),
+        _ => panic!("expected raw content")
+    }
+}
+
+#[test]
+fn test_extension_list_invalid_block_size() {
+    // The first block is smaller than a block header
+    let buffer: &[u8] = &[
+        0x04,0x00,0x00,0x00,0xFE,0x00,0xEF,0xBE,0x00,0x00
+    ];
+    assert!(ExtensionList::new(Cursor::new(buffer), 10).unwrap().is_empty());
+
+    // The first block is larger than the list
+    let buffer: &[u8] = &[
+        0x20,0x00,0x00,0x00,0xFE,0x00,0xEF,0xBE,0x00,0x00
+    ];
+    assert!(ExtensionList::new(Cursor::new(buffer), 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_beef0026() {
+    let buffer: &[u8] = &[
+        0x26,0x00,0x01,0x00,0x26,0x00,0xEF,0xBE,0x11,0x00,0x00,0x00,0xF6,0xE5,0xD4,0xC3,
+        0xB2,0xA1,0xD0,0x01,0xF7,0xE5,0xD4,0xC3,0xB2,0xA1,0xD0,0x01,0xF8,0xE5,0xD4,0xC3,
+        0xB2,0xA1,0xD0,0x01,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0026(ref beef0026)) => {
+            assert_eq!(beef0026.unknown1,0x11);
+            assert_eq!(beef0026.creation.0,0x01D0A1B2C3D4E5F6);
+            assert_eq!(beef0026.modification.0,0x01D0A1B2C3D4E5F7);
+            assert_eq!(beef0026.last_access.0,0x01D0A1B2C3D4E5F8);
+            assert_eq!(beef0026.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0026 block")
+    }
+}
+
+#[test]
+fn test_beef0025() {
+    let buffer: &[u8] = &[
+        0x1E,0x00,0x00,0x00,0x25,0x00,0xEF,0xBE,0x01,0x00,0x00,0x00,0xF6,0xE5,0xD4,0xC3,
+        0xB2,0xA1,0xD0,0x01,0xF7,0xE5,0xD4,0xC3,0xB2,0xA1,0xD0,0x01,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0025(ref beef0025)) => {
+            assert_eq!(beef0025.flags,0x01);
+            assert_eq!(beef0025.filetime1.0,0x01D0A1B2C3D4E5F6);
+            assert_eq!(beef0025.filetime2.0,0x01D0A1B2C3D4E5F7);
+            assert_eq!(beef0025.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0025 block")
+    }
+}
+
+#[test]
+fn test_beef0014() {
+    // A single uri property "ftp" followed by an empty property
+    let buffer: &[u8] = &[
+        0x30,0x00,0x00,0x00,0x14,0x00,0xEF,0xBE,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,
+        0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x01,0x00,0x00,0x00,0x06,0x00,0x00,0x00,
+        0x66,0x00,0x74,0x00,0x70,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0014(ref beef0014)) => {
+            assert_eq!(format!("{}",beef0014.guid),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+            assert_eq!(beef0014.properties.len(),1);
+            assert_eq!(beef0014.properties[0].identifier,1);
+            assert_eq!(beef0014.properties[0].value,"ftp");
+            assert_eq!(beef0014.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0014 block")
+    }
+}
+
+#[test]
+fn test_beef0013() {
+    let buffer: &[u8] = &[
+        0x1E,0x00,0x00,0x00,0x13,0x00,0xEF,0xBE,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,
+        0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x01,0x02,0x03,0x04,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0013(ref beef0013)) => {
+            assert_eq!(format!("{}",beef0013.guid),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+            assert_eq!(beef0013.unknown.as_ref().unwrap().0,vec![0x01,0x02,0x03,0x04]);
+            assert_eq!(beef0013.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0013 block")
+    }
+}
+
+#[test]
+fn test_beef0019() {
+    let buffer: &[u8] = &[
+        0x2A,0x00,0x00,0x00,0x19,0x00,0xEF,0xBE,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,
+        0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,
+        0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0019(ref beef0019)) => {
+            assert_eq!(format!("{}",beef0019.guid1),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+            assert_eq!(format!("{}",beef0019.guid2),"F29F85E0-4FF9-1068-AB91-08002B27B3D9");
+            assert!(beef0019.unknown.is_none());
+            assert_eq!(beef0019.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0019 block")
+    }
+}
+
+#[test]
+fn test_beef0003() {
+    // A block without room for a version offset keeps all of its guid
+    let buffer: &[u8] = &[
+        0x18,0x00,0x00,0x00,0x03,0x00,0xEF,0xBE,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,
+        0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0003(ref beef0003)) => {
+            assert_eq!(format!("{}",beef0003.guid),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+            assert_eq!(beef0003.version_offset,None);
+        },
+        _ => panic!("expected a Beef0003 block")
+    }
+}
+
+#[test]
+fn test_beef0005() {
+    // An identifier followed by a list holding a My Computer root folder item
+    let buffer: &[u8] = &[
+        0x30,0x00,0x00,0x00,0x05,0x00,0xEF,0xBE,0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,
+        0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9,0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,
+        0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x00,0x00,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0005(ref beef0005)) => {
+            assert_eq!(format!("{}",beef0005.guid),"F29F85E0-4FF9-1068-AB91-08002B27B3D9");
+            assert_eq!(beef0005.shell_items.len(),1);
+            assert_eq!(beef0005.shell_items.to_path(),"My Computer");
+            assert_eq!(beef0005.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0005 block")
+    }
+}
+
+#[test]
+fn test_beef0006() {
+    let buffer: &[u8] = &[
+        0x12,0x00,0x00,0x00,0x06,0x00,0xEF,0xBE,0x62,0x00,0x6F,0x00,0x62,0x00,0x00,0x00,
+        0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0006(ref beef0006)) => {
+            assert_eq!(beef0006.user_name,"bob");
+            assert_eq!(beef0006.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0006 block")
+    }
+}
+
+#[test]
+fn test_beef0016() {
+    let buffer: &[u8] = &[
+        0x32,0x00,0x00,0x00,0x16,0x00,0xEF,0xBE,0x40,0x00,0x73,0x00,0x68,0x00,0x65,0x00,
+        0x6C,0x00,0x6C,0x00,0x33,0x00,0x32,0x00,0x2E,0x00,0x64,0x00,0x6C,0x00,0x6C,0x00,
+        0x2C,0x00,0x2D,0x00,0x32,0x00,0x31,0x00,0x37,0x00,0x39,0x00,0x38,0x00,0x00,0x00,
+        0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0016(ref beef0016)) => {
+            assert_eq!(beef0016.localized_name,"@shell32.dll,-21798");
+            assert_eq!(beef0016.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0016 block")
+    }
+}
+
+#[test]
+fn test_beef0017() {
+    let buffer: &[u8] = &[
+        0x12,0x00,0x00,0x00,0x17,0x00,0xEF,0xBE,0x11,0x00,0x00,0x00,0x01,0x02,0x03,0x04,
+        0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0017(ref beef0017)) => {
+            assert_eq!(beef0017.flags,0x11);
+            assert_eq!(beef0017.unknown.as_ref().unwrap().0,vec![0x01,0x02,0x03,0x04]);
+            assert_eq!(beef0017.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0017 block")
+    }
+}
+
+#[test]
+fn test_beef0029() {
+    let buffer: &[u8] = &[
+        0x20,0x00,0x00,0x00,0x29,0x00,0xEF,0xBE,0x10,0x00,0x00,0x00,0xE0,0x4F,0xD0,0x20,
+        0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x01,0x02,0x14,0x00
+    ];
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.content {
+        Some(ExtensionContent::Beef0029(ref beef0029)) => {
+            assert_eq!(beef0029.unknown1,0x10);
+            assert_eq!(format!("{}",beef0029.guid),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+            assert_eq!(beef0029.unknown.as_ref().unwrap().0,vec![0x01,0x02]);
+            assert_eq!(beef0029.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0029 block")
+    }
+}
+
+#[test]
+fn test_extension_block_parse_error() {
+    // A Beef0017 block too small for its flags followed by data that does not
+    // belong to the block
+    let buffer: &[u8] = &[
+        0x0A,0x00,0x00,0x00,0x17,0x00,0xEF,0xBE,0x01,0x02,0xFF,0xFF
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),10);
+    match extension_block.content {
+        Some(ExtensionContent::Raw(ref raw)) => assert_eq!(raw.0,vec![0x01,0x02]),
+        _ => panic!("expected raw content")
+    }
+    match extension_block.get_anomalies().first() {
+        Some(&Anomaly::ParseError { offset, .. }) => assert_eq!(offset,8),
+        _ => panic!("expected a parse error anomaly")
+    }
+}
+
+#[test]
+fn test_beef0004_version_3() {
+    // Name "A" followed by the ascii localized name "@b,1" and an align byte
+    let buffer: &[u8] = &[
+        0x20,0x00,0x03,0x00,0x04,0x00,0xEF,0xBE,0x11,0x11,0x11,0x11,0x22,0x22,0x22,0x22,
+        0x14,0x00,0x05,0x00,0x41,0x00,0x00,0x00,0x40,0x62,0x2C,0x31,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),32);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::FileEntry(ref beef0004)) => {
+            assert_eq!(beef0004.identifier,0x14);
+            assert!(beef0004.file_reference.is_none());
+            assert_eq!(beef0004.get_name(),Some("A"));
+            assert_eq!(beef0004.localized_name,Some(String::from("@b,1")));
+            assert_eq!(beef0004.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0004 block")
+    }
+}
+
+#[test]
+fn test_beef0004_version_7() {
+    let buffer: &[u8] = &[
+        0x36,0x00,0x07,0x00,0x04,0x00,0xEF,0xBE,0x11,0x11,0x11,0x11,0x22,0x22,0x22,0x22,
+        0x14,0x00,0x00,0x00,0x20,0x00,0x00,0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x0A,0x00,0x41,0x00,0x00,0x00,0x40,0x00,0x62,0x00,0x2C,0x00,
+        0x31,0x00,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),54);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::FileEntry(ref beef0004)) => {
+            assert_eq!(beef0004.file_reference.as_ref().unwrap().0,0x0001000000000020);
+            assert!(beef0004.unknown3.is_none());
+            assert_eq!(beef0004.get_name(),Some("A"));
+            assert_eq!(beef0004.localized_name,Some(String::from("@b,1")));
+            assert_eq!(beef0004.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0004 block")
+    }
+}
+
+#[test]
+fn test_beef0004_version_8() {
+    let buffer: &[u8] = &[
+        0x3A,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x11,0x11,0x11,0x11,0x22,0x22,0x22,0x22,
+        0x14,0x00,0x00,0x00,0x20,0x00,0x00,0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x0A,0x00,0x33,0x33,0x33,0x33,0x41,0x00,0x00,0x00,0x40,0x00,
+        0x62,0x00,0x2C,0x00,0x31,0x00,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),58);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::FileEntry(ref beef0004)) => {
+            assert_eq!(beef0004.file_reference.as_ref().unwrap().0,0x0001000000000020);
+            assert_eq!(beef0004.unknown3,Some(0x33333333));
+            assert!(beef0004.unknown4.is_none());
+            assert_eq!(beef0004.get_name(),Some("A"));
+            assert_eq!(beef0004.localized_name,Some(String::from("@b,1")));
+            assert_eq!(beef0004.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0004 block")
+    }
+}
+
+#[test]
+fn test_beef0004_version_9() {
+    let buffer: &[u8] = &[
+        0x3E,0x00,0x09,0x00,0x04,0x00,0xEF,0xBE,0x11,0x11,0x11,0x11,0x22,0x22,0x22,0x22,
+        0x14,0x00,0x00,0x00,0x20,0x00,0x00,0x00,0x00,0x00,0x01,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x0A,0x00,0x33,0x33,0x33,0x33,0x44,0x44,0x44,0x44,0x41,0x00,
+        0x00,0x00,0x40,0x00,0x62,0x00,0x2C,0x00,0x31,0x00,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),62);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::FileEntry(ref beef0004)) => {
+            assert_eq!(beef0004.file_reference.as_ref().unwrap().0,0x0001000000000020);
+            assert_eq!(beef0004.unknown3,Some(0x33333333));
+            assert_eq!(beef0004.unknown4,Some(0x44444444));
+            assert_eq!(beef0004.get_name(),Some("A"));
+            assert_eq!(beef0004.localized_name,Some(String::from("@b,1")));
+            assert_eq!(beef0004.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef0004 block")
+    }
+}
+
+#[test]
+fn test_beef000a() {
+    // A guid followed by a list holding a My Computer root folder item
+    let buffer: &[u8] = &[
+        0x30,0x00,0x00,0x00,0x0A,0x00,0xEF,0xBE,0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,
+        0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9,0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,
+        0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),48);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::Beef000A(ref beef000a)) => {
+            assert_eq!(format!("{}",beef000a.guid),"F29F85E0-4FF9-1068-AB91-08002B27B3D9");
+            assert_eq!(beef000a.shell_items.len(),1);
+            assert_eq!(beef000a.shell_items.to_path(),"My Computer");
+            assert_eq!(beef000a.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef000A block")
+    }
+}
+
+#[test]
+fn test_beef000c() {
+    // A list holding a My Computer root folder item
+    let buffer: &[u8] = &[
+        0x20,0x00,0x00,0x00,0x0C,0x00,0xEF,0xBE,0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,
+        0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,0x00,0x00,0x14,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),32);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::Beef000C(ref beef000c)) => {
+            assert_eq!(beef000c.shell_items.len(),1);
+            assert_eq!(beef000c.shell_items.to_path(),"My Computer");
+            assert_eq!(beef000c.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef000C block")
+    }
+}
+
+#[test]
+fn test_beef000e() {
+    use shellitem::{ShellContent, MAX_NESTING_DEPTH};
+
+    // Builds a Beef000E block whose list holds a My Computer root folder item,
+    // which in turn carries the block of the previous level
+    fn nested_blocks(levels: usize) -> Vec<u8> {
+        let mut item: Vec<u8> = vec![
+            0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+            0x2B,0x30,0x30,0x9D
+        ];
+        let mut block = Vec::new();
+        for level in 0..levels {
+            if level > 0 {
+                let size = (block.len() + 20) as u16;
+                item = vec![size as u8,(size >> 8) as u8];
+                item.extend_from_slice(&[
+                    0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+                    0x2B,0x30,0x30,0x9D
+                ]);
+                item.extend_from_slice(&block);
+            }
+
+            let size = (item.len() + 28) as u16;
+            block = vec![size as u8,(size >> 8) as u8,0x00,0x00,0x0E,0x00,0xEF,0xBE];
+            block.extend_from_slice(&[
+                0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9
+            ]);
+            block.extend_from_slice(&item);
+            block.extend_from_slice(&[0x00,0x00,0x14,0x00]);
+        }
+        block
+    }
+
+    // Returns the list of the Beef000E block carried by the first item of `list`
+    fn inner_list(list: &ShellList) -> &ShellList {
+        let item = list[0].get_item().unwrap();
+        match item.data.as_ref().unwrap().content {
+            ShellContent::RootFolder(ref root_folder) => {
+                match root_folder.extension_blocks.as_ref().unwrap()[0].content {
+                    Some(ExtensionContent::Beef000E(ref beef000e)) => &beef000e.shell_items,
+                    _ => panic!("expected a Beef000E block")
+                }
+            },
+            _ => panic!("expected a root folder item")
+        }
+    }
+
+    let buffer = nested_blocks(1);
+    let mut reader = Cursor::new(&buffer[..]);
+    let extension_block = ExtensionBlock::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),buffer.len() as u64);
+    assert!(extension_block.get_anomalies().is_empty());
+    match extension_block.content {
+        Some(ExtensionContent::Beef000E(ref beef000e)) => {
+            assert_eq!(format!("{}",beef000e.guid),"F29F85E0-4FF9-1068-AB91-08002B27B3D9");
+            assert_eq!(beef000e.shell_items.to_path(),"My Computer");
+            assert!(beef000e.property_store.is_none());
+            assert_eq!(beef000e.version_offset,Some(0x14));
+        },
+        _ => panic!("expected a Beef000E block")
+    }
+
+    // every level nests one more shell item, the item past the limit fails
+    // within the innermost list
+    for &(levels, fails) in [(MAX_NESTING_DEPTH, false), (MAX_NESTING_DEPTH + 1, true)].iter() {
+        let extension_block = ExtensionBlock::new(Cursor::new(nested_blocks(levels))).unwrap();
+        let mut list = match extension_block.content {
+            Some(ExtensionContent::Beef000E(ref beef000e)) => &beef000e.shell_items,
+            _ => panic!("expected a Beef000E block")
+        };
+        for _ in 1..levels {
+            list = inner_list(list);
+        }
+
+        assert_eq!(list.len(),1);
+        assert_eq!(list[0].is_failed(),fails);
+        if fails {
+            assert_eq!(list.get_failed_items()[0].error.kind,ErrorKind::MaxDepthExceeded);
+        }
+    }
 }
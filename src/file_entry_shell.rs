@@ -6,7 +6,7 @@ use std::io::{Seek,SeekFrom};
 use std::fmt;
 use serde::{ser};
 use shellitem::{ClassType};
-use extension_blocks::{ExtensionBlock};
+use extension_blocks::{ExtensionList};
 use utils;
 
 pub static mut FLAGS_AS_INT: bool = false;
@@ -84,10 +84,10 @@ pub struct FileEntryShellItem {
     pub last_modification: DosDateTime,
     pub flags: FileAttributeFlags,
     pub name: String,
-    pub extention_block: ExtensionBlock
+    pub extension_blocks: ExtensionList
 }
 impl FileEntryShellItem {
-    pub fn new<Rs: Read+Seek>(mut reader: Rs, class_type: &ClassType) -> Result<FileEntryShellItem,ShellItemError> {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, class_type: &ClassType, size: u16) -> Result<FileEntryShellItem,ShellItemError> {
         let _offset = reader.seek(SeekFrom::Current(0))?;
         let sub_flags = FileEntryItemFlags::from_bits_truncate(
             class_type.get_minor()
@@ -118,10 +118,14 @@ impl FileEntryShellItem {
             }
         }
 
-        // Get extention block
-        let extention_block = ExtensionBlock::new(
-            &mut reader
+        // Get extention blocks from what is left of the item. The offset is 4 bytes
+        // into the item as size(2), class_type(1) and unknown(1) have been read.
+        let consumed = reader.seek(SeekFrom::Current(0))? - _offset + 4;
+        let mut extension_blocks = ExtensionList::new(
+            &mut reader,
+            (size as u64).saturating_sub(consumed)
         )?;
+        extension_blocks.check_version_offsets(consumed);
 
         Ok(
             FileEntryShellItem {
@@ -131,10 +135,22 @@ impl FileEntryShellItem {
                 last_modification: last_modification,
                 flags: flags,
                 name: name,
-                extention_block: extention_block
+                extension_blocks: extension_blocks
             }
         )
     }
+
+    // Prefers the long name of the Beef0004 extension block over the 8.3 name
+    pub fn get_name(&self, long_names: bool) -> &str {
+        if long_names {
+            if let Some(name) = self.extension_blocks.get_long_name() {
+                if !name.is_empty() {
+                    return name;
+                }
+            }
+        }
+        &self.name
+    }
 }
 
 #[test]
@@ -153,7 +169,8 @@ fn test_file_entry_item() {
 
     let file_entry = FileEntryShellItem::new(
         Cursor::new(buffer),
-        &ClassType::new(0x32)
+        &ClassType::new(0x32),
+        0x80
     ).unwrap();
     assert_eq!(file_entry.file_size,68346);
 }
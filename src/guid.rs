@@ -0,0 +1,62 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use serde::{ser};
+use errors::{ShellItemError};
+use std::io::Read;
+use std::fmt;
+
+// GUIDs are stored as a little-endian u32, two little-endian u16s and 8 bytes
+#[derive(Clone, PartialEq)]
+pub struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8]
+}
+impl Guid {
+    pub fn new<R: Read>(mut reader: R) -> Result<Guid,ShellItemError> {
+        let data1 = reader.read_u32::<LittleEndian>()?;
+        let data2 = reader.read_u16::<LittleEndian>()?;
+        let data3 = reader.read_u16::<LittleEndian>()?;
+        let mut data4 = [0; 8];
+        reader.read_exact(&mut data4)?;
+
+        Ok(
+            Guid {
+                data1: data1,
+                data2: data2,
+                data3: data3,
+                data4: data4
+            }
+        )
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.data1 == 0 && self.data2 == 0 && self.data3 == 0 &&
+            self.data4.iter().all(|b| *b == 0)
+    }
+}
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+            self.data1, self.data2, self.data3,
+            self.data4[0], self.data4[1], self.data4[2], self.data4[3],
+            self.data4[4], self.data4[5], self.data4[6], self.data4[7]
+        )
+    }
+}
+impl fmt::Debug for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self)
+    }
+}
+impl ser::Serialize for Guid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        serializer.serialize_str(
+            &format!("{}", self)
+        )
+    }
+}
@@ -2,14 +2,27 @@
 #[macro_use] extern crate trace_error;
 #[macro_use] extern crate bitflags;
 #[macro_use] extern crate log;
+#[macro_use] extern crate lazy_static;
 extern crate rwinstructs;
 extern crate chrono;
 extern crate serde;
+extern crate serde_json;
 extern crate byteorder;
 extern crate encoding;
 pub mod errors;
 pub mod shellitem;
 pub mod shelllist;
+pub mod guid;
+pub mod root_folder_shell;
+pub mod volume_shell;
+pub mod network_shell;
+pub mod uri_shell;
+pub mod control_panel_shell;
+pub mod delegate_shell;
+pub mod compressed_folder_shell;
 pub mod file_entry_shell;
 pub mod extension_blocks;
+pub mod property_store;
+pub mod borrowed;
+pub mod registry;
 pub mod utils;
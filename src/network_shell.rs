@@ -0,0 +1,170 @@
+use byteorder::{ReadBytesExt};
+use errors::{ShellItemError};
+use file_entry_shell::{FLAGS_AS_INT};
+use shellitem::{ClassType};
+use utils;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::fmt;
+use serde::{ser};
+
+bitflags! {
+    pub struct NetworkLocationFlags: u8 {
+        const HAS_COMMENTS         = 0x40;
+        const HAS_DESCRIPTION      = 0x80;
+    }
+}
+impl fmt::Display for NetworkLocationFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.bits())
+    }
+}
+impl ser::Serialize for NetworkLocationFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        if unsafe{FLAGS_AS_INT} {
+            serializer.serialize_u8(self.bits())
+        } else {
+            serializer.serialize_str(&format!("{:?}", self))
+        }
+    }
+}
+
+// The network type is stored in the lower 4 bits of the class type
+#[derive(Clone)]
+pub struct NetworkType(
+    u8
+);
+impl NetworkType {
+    pub fn new(value: u8) -> NetworkType {
+        NetworkType(value)
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        self.0
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self.0 {
+            0x01 => "DOMAIN",
+            0x02 => "SERVER",
+            0x03 => "SHARE",
+            0x06 => "MICROSOFT_WINDOWS_NETWORK",
+            0x07 => "ENTIRE_NETWORK",
+            _ => "UNKNOWN"
+        }
+    }
+}
+impl fmt::Display for NetworkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.get_name())
+    }
+}
+impl fmt::Debug for NetworkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{} (0x{:02X})",self.get_name(),self.0)
+    }
+}
+impl ser::Serialize for NetworkType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        serializer.serialize_str(
+            &format!("{}", self)
+        )
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#36-network-location-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct NetworkLocationShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub network_type: NetworkType,
+    pub flags: NetworkLocationFlags,
+    pub location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>
+}
+impl NetworkLocationShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, class_type: &ClassType, size: u16) -> Result<NetworkLocationShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let network_type = NetworkType::new(
+            class_type.get_minor()
+        );
+        let flags = NetworkLocationFlags::from_bits_truncate(
+            reader.read_u8()?
+        );
+
+        // subtract 5 from size to account for size(2), class_type(1), unknown(1) and flags(1)
+        let mut buffer = vec![0; size.saturating_sub(5) as usize];
+        reader.read_exact(&mut buffer)?;
+        let mut content_reader = Cursor::new(buffer);
+
+        let location = utils::read_string_u8_till_null_lossy(
+            &mut content_reader
+        )?;
+
+        let mut description = None;
+        if flags.contains(HAS_DESCRIPTION) {
+            description = Some(
+                utils::read_string_u8_till_null_lossy(&mut content_reader)?
+            );
+        }
+
+        let mut comments = None;
+        if flags.contains(HAS_COMMENTS) {
+            comments = Some(
+                utils::read_string_u8_till_null_lossy(&mut content_reader)?
+            );
+        }
+
+        Ok(
+            NetworkLocationShellItem {
+                _offset: _offset,
+                network_type: network_type,
+                flags: flags,
+                location: location,
+                description: description,
+                comments: comments
+            }
+        )
+    }
+}
+
+#[test]
+fn test_network_location_item() {
+    use std::io::Cursor;
+    // flags, "\\srv\sh", "Desc" and "Note"
+    let buffer: &[u8] = &[
+        0xC0,0x5C,0x5C,0x73,0x72,0x76,0x5C,0x73,0x68,0x00,0x44,0x65,0x73,0x63,0x00,0x4E,
+        0x6F,0x74,0x65,0x00
+    ];
+
+    let network_item = NetworkLocationShellItem::new(
+        Cursor::new(buffer), &ClassType::new(0x43), 24
+    ).unwrap();
+    assert_eq!(network_item.network_type.get_name(),"SHARE");
+    assert!(network_item.flags.contains(HAS_DESCRIPTION | HAS_COMMENTS));
+    assert_eq!(network_item.location,"\\\\srv\\sh");
+    assert_eq!(network_item.description,Some(String::from("Desc")));
+    assert_eq!(network_item.comments,Some(String::from("Note")));
+}
+
+#[test]
+fn test_network_location_codepage_name() {
+    use std::io::Cursor;
+    // "\\srv\caf\xE9" with a codepage 1252 character
+    let buffer: &[u8] = &[
+        0x00,0x5C,0x5C,0x73,0x72,0x76,0x5C,0x63,0x61,0x66,0xE9,0x00
+    ];
+
+    let network_item = NetworkLocationShellItem::new(
+        Cursor::new(buffer), &ClassType::new(0x43), 16
+    ).unwrap();
+    assert_eq!(network_item.location,"\\\\srv\\caf\u{FFFD}");
+}
@@ -0,0 +1,303 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use rwinstructs::timestamp::{WinTimestamp};
+use errors::{ShellItemError};
+use guid::{Guid};
+use shellitem::{RawContent};
+use utils;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::io::{Error,ErrorKind};
+
+// "1SPS"
+pub const PROPERTY_STORAGE_VERSION: u32 = 0x53505331;
+// Storages with this format identifier key their values by name instead of id
+const NAMED_PROPERTIES_FORMAT: &'static str = "D5CDD505-2E9C-101B-9397-08002B2CF9AE";
+
+pub const VT_EMPTY: u16 = 0x0000;
+pub const VT_NULL: u16 = 0x0001;
+pub const VT_I2: u16 = 0x0002;
+pub const VT_I4: u16 = 0x0003;
+pub const VT_R4: u16 = 0x0004;
+pub const VT_R8: u16 = 0x0005;
+pub const VT_BSTR: u16 = 0x0008;
+pub const VT_BOOL: u16 = 0x000B;
+pub const VT_I1: u16 = 0x0010;
+pub const VT_UI1: u16 = 0x0011;
+pub const VT_UI2: u16 = 0x0012;
+pub const VT_UI4: u16 = 0x0013;
+pub const VT_I8: u16 = 0x0014;
+pub const VT_UI8: u16 = 0x0015;
+pub const VT_INT: u16 = 0x0016;
+pub const VT_UINT: u16 = 0x0017;
+pub const VT_LPSTR: u16 = 0x001E;
+pub const VT_LPWSTR: u16 = 0x001F;
+pub const VT_FILETIME: u16 = 0x0040;
+pub const VT_BLOB: u16 = 0x0041;
+pub const VT_CLSID: u16 = 0x0048;
+
+// Value types this crate does not decode keep their type and raw data
+#[derive(Serialize, Clone, Debug)]
+pub struct UnhandledValue {
+    pub value_type: u16,
+    pub data: RawContent
+}
+
+// Reads a buffer whose size was read from the data. Sizes larger than what is left
+// in the reader are an error instead of an allocation of up to 4 GB.
+fn read_sized_buffer(reader: &mut Cursor<Vec<u8>>, size: u64) -> Result<Vec<u8>,ShellItemError> {
+    let remaining = (reader.get_ref().len() as u64).saturating_sub(reader.position());
+    if size > remaining {
+        return Err(
+            ShellItemError::from(
+                Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("Size {} exceeds the {} bytes left in the property value", size, remaining)
+                )
+            )
+        );
+    }
+
+    let mut buffer = vec![0; size as usize];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Bool(bool),
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+    String(String),
+    FileTime(WinTimestamp),
+    Guid(Guid),
+    Blob(RawContent),
+    Unhandled(UnhandledValue),
+    Empty
+}
+impl PropertyValue {
+    // Reads a typed property value (PROPVARIANT)
+    pub fn new(reader: &mut Cursor<Vec<u8>>) -> Result<PropertyValue,ShellItemError> {
+        let value_type = reader.read_u16::<LittleEndian>()?;
+        let _padding = reader.read_u16::<LittleEndian>()?;
+
+        let value = match value_type {
+            VT_EMPTY | VT_NULL => PropertyValue::Empty,
+            VT_I1 => PropertyValue::Signed(reader.read_i8()? as i64),
+            VT_I2 => PropertyValue::Signed(reader.read_i16::<LittleEndian>()? as i64),
+            VT_I4 | VT_INT => PropertyValue::Signed(reader.read_i32::<LittleEndian>()? as i64),
+            VT_I8 => PropertyValue::Signed(reader.read_i64::<LittleEndian>()?),
+            VT_UI1 => PropertyValue::Unsigned(reader.read_u8()? as u64),
+            VT_UI2 => PropertyValue::Unsigned(reader.read_u16::<LittleEndian>()? as u64),
+            VT_UI4 | VT_UINT => PropertyValue::Unsigned(reader.read_u32::<LittleEndian>()? as u64),
+            VT_UI8 => PropertyValue::Unsigned(reader.read_u64::<LittleEndian>()?),
+            VT_R4 => PropertyValue::Float(reader.read_f32::<LittleEndian>()? as f64),
+            VT_R8 => PropertyValue::Float(reader.read_f64::<LittleEndian>()?),
+            VT_BOOL => PropertyValue::Bool(reader.read_i16::<LittleEndian>()? != 0),
+            VT_BSTR => {
+                // size is in bytes
+                let size = reader.read_u32::<LittleEndian>()? as u64;
+                let buffer = read_sized_buffer(reader, size)?;
+                PropertyValue::String(utils::decode_utf16le(&buffer)?)
+            },
+            VT_LPWSTR => {
+                // size is in characters and includes the null char
+                let size = reader.read_u32::<LittleEndian>()? as u64;
+                let buffer = read_sized_buffer(reader, size * 2)?;
+                PropertyValue::String(utils::decode_utf16le(&buffer)?)
+            },
+            VT_LPSTR => {
+                let size = reader.read_u32::<LittleEndian>()? as u64;
+                let buffer = read_sized_buffer(reader, size)?;
+                let string_buffer: Vec<u8> = buffer.into_iter()
+                    .take_while(|b| *b != 0x00)
+                    .collect();
+                PropertyValue::String(String::from_utf8_lossy(&string_buffer).into_owned())
+            },
+            VT_FILETIME => PropertyValue::FileTime(
+                WinTimestamp(reader.read_u64::<LittleEndian>()?)
+            ),
+            VT_CLSID => PropertyValue::Guid(
+                Guid::new(&mut *reader)?
+            ),
+            VT_BLOB => {
+                let size = reader.read_u32::<LittleEndian>()? as u64;
+                let buffer = read_sized_buffer(reader, size)?;
+                PropertyValue::Blob(RawContent(buffer))
+            },
+            _ => {
+                let mut buffer = Vec::new();
+                reader.read_to_end(&mut buffer)?;
+                PropertyValue::Unhandled(
+                    UnhandledValue {
+                        value_type: value_type,
+                        data: RawContent(buffer)
+                    }
+                )
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub value: PropertyValue
+}
+
+//https://github.com/libyal/libfole/blob/master/documentation/OLE%20definitions.asciidoc#serialized-property-storage
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyStorage {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    #[serde(skip_serializing)]
+    pub size: u32,
+    pub format_id: Guid,
+    pub values: Vec<PropertyEntry>
+}
+impl PropertyStorage {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<PropertyStorage,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let size = reader.read_u32::<LittleEndian>()?;
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != PROPERTY_STORAGE_VERSION {
+            return Err(
+                ShellItemError::invalid_signature(
+                    format!("Invalid property storage version 0x{:08X} at offset {}",version,_offset)
+                )
+            );
+        }
+        let format_id = Guid::new(&mut reader)?;
+        let named = format!("{}", format_id) == NAMED_PROPERTIES_FORMAT;
+
+        let mut values: Vec<PropertyEntry> = Vec::new();
+        loop {
+            let value_size = reader.read_u32::<LittleEndian>()?;
+            if value_size == 0 {
+                // Null value is terminator
+                break
+            }
+
+            let value_offset = reader.seek(SeekFrom::Current(0))?;
+            let storage_remaining = (_offset + size as u64).saturating_sub(value_offset);
+            if value_size.saturating_sub(4) as u64 > storage_remaining {
+                return Err(
+                    ShellItemError::from(
+                        Error::new(
+                            ErrorKind::UnexpectedEof,
+                            format!("Value at offset {} exceeds the property storage size {}", value_offset, size)
+                        )
+                    )
+                );
+            }
+
+            // subtract 4 from value_size to account for value_size(4)
+            let mut buffer = vec![0; value_size.saturating_sub(4) as usize];
+            reader.read_exact(&mut buffer)?;
+            let mut value_reader = Cursor::new(buffer);
+
+            let mut id = None;
+            let mut name = None;
+            if named {
+                let name_size = value_reader.read_u32::<LittleEndian>()? as u64;
+                let _reserved = value_reader.read_u8()?;
+                let name_buffer = read_sized_buffer(&mut value_reader, name_size)?;
+                name = Some(utils::decode_utf16le(&name_buffer)?);
+            } else {
+                id = Some(value_reader.read_u32::<LittleEndian>()?);
+                let _reserved = value_reader.read_u8()?;
+            }
+
+            let value = PropertyValue::new(&mut value_reader)?;
+            values.push(
+                PropertyEntry {
+                    id: id,
+                    name: name,
+                    value: value
+                }
+            );
+        }
+
+        // always continue with the next storage regardless of how much was read
+        reader.seek(SeekFrom::Start(_offset + size as u64))?;
+
+        Ok(
+            PropertyStorage {
+                _offset: _offset,
+                size: size,
+                format_id: format_id,
+                values: values
+            }
+        )
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyStore(
+    Vec<PropertyStorage>
+);
+impl PropertyStore {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<PropertyStore,ShellItemError> {
+        let mut storages: Vec<PropertyStorage> = Vec::new();
+        loop {
+            let size = utils::peek_u32(&mut reader)?;
+            if size == 0 {
+                // Null storage is terminator
+                break
+            }
+
+            storages.push(
+                PropertyStorage::new(&mut reader)?
+            );
+        }
+
+        Ok(
+            PropertyStore(storages)
+        )
+    }
+
+    pub fn from_buffer(buffer: &[u8]) -> Result<PropertyStore,ShellItemError> {
+        PropertyStore::new(Cursor::new(buffer))
+    }
+}
+
+#[test]
+fn test_property_store() {
+    let buffer: &[u8] = &[
+        0x2D,0x00,0x00,0x00,0x31,0x53,0x50,0x53,0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,
+        0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9,0x11,0x00,0x00,0x00,0x02,0x00,0x00,0x00,
+        0x00,0x13,0x00,0x00,0x00,0xD2,0x04,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00
+    ];
+
+    let property_store = PropertyStore::from_buffer(buffer).unwrap();
+    let storage = &property_store.0[0];
+    assert_eq!(format!("{}",storage.format_id),"F29F85E0-4FF9-1068-AB91-08002B27B3D9");
+    assert_eq!(storage.values[0].id,Some(2));
+    match storage.values[0].value {
+        PropertyValue::Unsigned(value) => assert_eq!(value,1234),
+        _ => panic!("expected an unsigned value")
+    }
+}
+
+#[test]
+fn test_property_value_size_exceeds_data() {
+    use errors::{ErrorKind as ShellItemErrorKind};
+    // VT_LPWSTR value with a size of 0xFFFFFFFF characters
+    let buffer: &[u8] = &[
+        0x2D,0x00,0x00,0x00,0x31,0x53,0x50,0x53,0xE0,0x85,0x9F,0xF2,0xF9,0x4F,0x68,0x10,
+        0xAB,0x91,0x08,0x00,0x2B,0x27,0xB3,0xD9,0x11,0x00,0x00,0x00,0x02,0x00,0x00,0x00,
+        0x00,0x1F,0x00,0x00,0x00,0xFF,0xFF,0xFF,0xFF,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00
+    ];
+
+    let error = PropertyStore::from_buffer(buffer).unwrap_err();
+    assert_eq!(error.kind,ShellItemErrorKind::IoError);
+}
@@ -0,0 +1,330 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use errors::{ShellItemError};
+use serde_json::{Value};
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::sync::{Arc,RwLock};
+
+// Parsers receive the block content following the 8 byte header and the block version
+pub type ExtensionParser = dyn Fn(&[u8], u16) -> Result<Value,ShellItemError> + Send + Sync;
+
+struct RegisteredExtensionParser {
+    signature: u32,
+    version: Option<u16>,
+    parser: Arc<ExtensionParser>
+}
+
+// Parsers for extension block signatures that this crate does not handle, or
+// handles differently than wanted. A parser registered for a specific version
+// takes precedence over one registered for any version (None).
+pub struct ExtensionParserRegistry {
+    parsers: Vec<RegisteredExtensionParser>
+}
+impl ExtensionParserRegistry {
+    pub fn new() -> ExtensionParserRegistry {
+        ExtensionParserRegistry {
+            parsers: Vec::new()
+        }
+    }
+
+    // Replaces any parser already registered for the same signature and version
+    pub fn register<F>(&mut self, signature: u32, version: Option<u16>, parser: F)
+        where F: Fn(&[u8], u16) -> Result<Value,ShellItemError> + Send + Sync + 'static
+    {
+        self.unregister(signature, version);
+        self.parsers.push(
+            RegisteredExtensionParser {
+                signature: signature,
+                version: version,
+                parser: Arc::new(parser)
+            }
+        );
+    }
+
+    pub fn unregister(&mut self, signature: u32, version: Option<u16>) {
+        self.parsers.retain(
+            |p| !(p.signature == signature && p.version == version)
+        );
+    }
+
+    pub fn get_parser(&self, signature: u32, version: u16) -> Option<Arc<ExtensionParser>> {
+        let exact = self.parsers.iter().find(
+            |p| p.signature == signature && p.version == Some(version)
+        );
+        let found = match exact {
+            Some(registered) => Some(registered),
+            None => self.parsers.iter().find(
+                |p| p.signature == signature && p.version.is_none()
+            )
+        };
+
+        found.map(|p| p.parser.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+}
+impl Default for ExtensionParserRegistry {
+    fn default() -> ExtensionParserRegistry {
+        ExtensionParserRegistry::new()
+    }
+}
+
+lazy_static! {
+    static ref EXTENSION_PARSERS: RwLock<ExtensionParserRegistry> = RwLock::new(
+        ExtensionParserRegistry::new()
+    );
+}
+
+// Registers a parser that is consulted by ExtensionBlock::new for all blocks parsed
+// after the call.
+pub fn register_extension_parser<F>(signature: u32, version: Option<u16>, parser: F)
+    where F: Fn(&[u8], u16) -> Result<Value,ShellItemError> + Send + Sync + 'static
+{
+    let mut registry = EXTENSION_PARSERS.write().unwrap_or_else(|e| e.into_inner());
+    registry.register(signature, version, parser);
+}
+
+pub fn unregister_extension_parser(signature: u32, version: Option<u16>) {
+    let mut registry = EXTENSION_PARSERS.write().unwrap_or_else(|e| e.into_inner());
+    registry.unregister(signature, version);
+}
+
+// Reads `size` bytes of block content and runs the registered parser on them.
+// Returns None without reading anything if no parser is registered.
+pub fn parse_extension<R: Read>(mut reader: R, signature: u32, version: u16, size: usize) -> Result<Option<Value>,ShellItemError> {
+    // the lock is released before parsing so parsers can parse nested blocks
+    let parser = match EXTENSION_PARSERS.read().unwrap_or_else(|e| e.into_inner()).get_parser(signature, version) {
+        Some(parser) => parser,
+        None => return Ok(None)
+    };
+
+    let mut buffer = vec![0; size];
+    reader.read_exact(&mut buffer)?;
+    Ok(
+        Some(parser(&buffer, version)?)
+    )
+}
+
+// Parsers receive the item data following size(2), class_type(1) and unknown(1),
+// the class type and the unknown byte
+pub type ShellItemParser = dyn Fn(&[u8], u8, u8) -> Result<Value,ShellItemError> + Send + Sync;
+
+// A signature that has to be present at `offset` relative to the start of the item
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignatureMatch {
+    pub offset: u16,
+    pub signature: u32
+}
+impl SignatureMatch {
+    pub fn new(offset: u16, signature: u32) -> SignatureMatch {
+        SignatureMatch {
+            offset: offset,
+            signature: signature
+        }
+    }
+
+    // `data` starts after the unknown byte (offset 4) of the item
+    pub fn is_match(&self, data: &[u8]) -> bool {
+        if self.offset < 4 {
+            return false;
+        }
+
+        let start = (self.offset - 4) as usize;
+        if data.len() < start + 4 {
+            return false;
+        }
+
+        match (&data[start..start + 4]).read_u32::<LittleEndian>() {
+            Ok(signature) => signature == self.signature,
+            Err(_) => false
+        }
+    }
+}
+
+struct RegisteredShellItemParser {
+    first: u8,
+    last: u8,
+    signature: Option<SignatureMatch>,
+    parser: Arc<ShellItemParser>
+}
+
+// Parsers for shell item class types, keyed by an inclusive class type range and
+// an optional signature. A parser whose signature matches takes precedence over
+// one registered without a signature.
+pub struct ShellItemParserRegistry {
+    parsers: Vec<RegisteredShellItemParser>
+}
+impl ShellItemParserRegistry {
+    pub fn new() -> ShellItemParserRegistry {
+        ShellItemParserRegistry {
+            parsers: Vec::new()
+        }
+    }
+
+    // Replaces any parser already registered for the same range and signature
+    pub fn register<F>(&mut self, first: u8, last: u8, signature: Option<SignatureMatch>, parser: F)
+        where F: Fn(&[u8], u8, u8) -> Result<Value,ShellItemError> + Send + Sync + 'static
+    {
+        self.unregister(first, last, signature);
+        self.parsers.push(
+            RegisteredShellItemParser {
+                first: first,
+                last: last,
+                signature: signature,
+                parser: Arc::new(parser)
+            }
+        );
+    }
+
+    pub fn unregister(&mut self, first: u8, last: u8, signature: Option<SignatureMatch>) {
+        self.parsers.retain(
+            |p| !(p.first == first && p.last == last && p.signature == signature)
+        );
+    }
+
+    pub fn has_class_type(&self, class_type: u8) -> bool {
+        self.parsers.iter().any(
+            |p| p.first <= class_type && class_type <= p.last
+        )
+    }
+
+    pub fn get_parser(&self, class_type: u8, data: &[u8]) -> Option<Arc<ShellItemParser>> {
+        let mut fallback = None;
+        for registered in self.parsers.iter() {
+            if class_type < registered.first || class_type > registered.last {
+                continue;
+            }
+
+            match registered.signature {
+                Some(ref signature) => {
+                    if signature.is_match(data) {
+                        return Some(registered.parser.clone());
+                    }
+                },
+                None => {
+                    if fallback.is_none() {
+                        fallback = Some(registered.parser.clone());
+                    }
+                }
+            }
+        }
+
+        fallback
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+}
+impl Default for ShellItemParserRegistry {
+    fn default() -> ShellItemParserRegistry {
+        ShellItemParserRegistry::new()
+    }
+}
+
+lazy_static! {
+    static ref SHELL_ITEM_PARSERS: RwLock<ShellItemParserRegistry> = RwLock::new(
+        ShellItemParserRegistry::new()
+    );
+}
+
+// Registers a parser that is consulted by ShellData::new for all items parsed
+// after the call.
+pub fn register_shell_item_parser<F>(first: u8, last: u8, signature: Option<SignatureMatch>, parser: F)
+    where F: Fn(&[u8], u8, u8) -> Result<Value,ShellItemError> + Send + Sync + 'static
+{
+    let mut registry = SHELL_ITEM_PARSERS.write().unwrap_or_else(|e| e.into_inner());
+    registry.register(first, last, signature, parser);
+}
+
+pub fn unregister_shell_item_parser(first: u8, last: u8, signature: Option<SignatureMatch>) {
+    let mut registry = SHELL_ITEM_PARSERS.write().unwrap_or_else(|e| e.into_inner());
+    registry.unregister(first, last, signature);
+}
+
+// Reads the item data following the unknown byte and runs the matching registered
+// parser on it. If no parser matches the reader is left where it was and None
+// is returned.
+pub fn parse_shell_item<Rs: Read+Seek>(mut reader: Rs, class_type: u8, unknown: u8, size: u16) -> Result<Option<Value>,ShellItemError> {
+    if !SHELL_ITEM_PARSERS.read().unwrap_or_else(|e| e.into_inner()).has_class_type(class_type) {
+        return Ok(None);
+    }
+
+    let _offset = reader.seek(SeekFrom::Current(0))?;
+    // subtract 4 from size to account for size(2), class_type(1), and unknown(1)
+    let mut buffer = vec![0; size.saturating_sub(4) as usize];
+    reader.read_exact(&mut buffer)?;
+
+    let parser = match SHELL_ITEM_PARSERS.read().unwrap_or_else(|e| e.into_inner()).get_parser(class_type, &buffer) {
+        Some(parser) => parser,
+        None => {
+            reader.seek(SeekFrom::Start(_offset))?;
+            return Ok(None);
+        }
+    };
+
+    Ok(
+        Some(parser(&buffer, class_type, unknown)?)
+    )
+}
+
+#[test]
+fn test_registered_extension_parser() {
+    use extension_blocks::{ExtensionBlock, ExtensionContent};
+    use std::io::Cursor;
+    // The registry is shared by all tests, so the parser is registered for a
+    // signature no other test uses and is unregistered even if the test fails
+    struct Registration;
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            unregister_extension_parser(0xBEEF00FF, Some(0xFF));
+        }
+    }
+
+    let buffer: &[u8] = &[
+        0x0C,0x00,0xFF,0x00,0xFF,0x00,0xEF,0xBE,0x41,0x42,0x43,0x00
+    ];
+
+    register_extension_parser(0xBEEF00FF, Some(0xFF), |data, _version| {
+        Ok(Value::String(String::from_utf8_lossy(&data[..3]).into_owned()))
+    });
+    let _registration = Registration;
+
+    let extension_block = ExtensionBlock::new(Cursor::new(buffer)).unwrap();
+    match extension_block.get_content() {
+        Some(&ExtensionContent::Custom(Value::String(ref value))) => assert_eq!(value,"ABC"),
+        _ => panic!("expected custom content")
+    }
+}
+
+#[test]
+fn test_registered_shell_item_parser() {
+    use shellitem::{ShellItem, ShellContent};
+    use std::io::Cursor;
+    // The registry is shared by all tests, so the parser is registered for a
+    // single class type and signature no other test uses and is unregistered
+    // even if the test fails
+    struct Registration;
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            unregister_shell_item_parser(0xE5, 0xE5, Some(SignatureMatch::new(4, 0x12345678)));
+        }
+    }
+
+    let buffer: &[u8] = &[
+        0x0C,0x00,0xE5,0x00,0x78,0x56,0x34,0x12,0x41,0x42,0x43,0x00
+    ];
+
+    register_shell_item_parser(0xE5, 0xE5, Some(SignatureMatch::new(4, 0x12345678)), |data, _class_type, _unknown| {
+        Ok(Value::String(String::from_utf8_lossy(&data[4..7]).into_owned()))
+    });
+    let _registration = Registration;
+
+    let shell_item = ShellItem::new(Cursor::new(buffer)).unwrap();
+    match shell_item.data.unwrap().content {
+        ShellContent::Custom(Value::String(ref value)) => assert_eq!(value,"ABC"),
+        _ => panic!("expected custom content")
+    }
+}
@@ -0,0 +1,159 @@
+use errors::{ShellItemError};
+use guid::{Guid};
+use extension_blocks::{ExtensionList};
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+
+// Returns the display name of a well known shell folder identifier
+pub fn get_shell_folder_name(guid: &Guid) -> Option<&'static str> {
+    match format!("{}", guid).as_str() {
+        "20D04FE0-3AEA-1069-A2D8-08002B30309D" => Some("My Computer"),
+        "450D8FBA-AD25-11D0-98A8-0800361B1103" => Some("My Documents"),
+        "208D2C60-3AEA-1069-A2D7-08002B30309D" => Some("My Network Places"),
+        "F02C1A0D-BE21-4350-88B0-7367FC96EF3C" => Some("Network"),
+        "645FF040-5081-101B-9F08-00AA002F954E" => Some("Recycle Bin"),
+        "871C5380-42A0-1069-A2EA-08002B30309D" => Some("Internet Explorer"),
+        "21EC2020-3AEA-1069-A2DD-08002B30309D" => Some("Control Panel"),
+        "26EE0668-A00A-44D7-9371-BEB064C98683" => Some("Control Panel"),
+        "2227A280-3AEA-1069-A2DE-08002B30309D" => Some("Printers"),
+        "59031A47-3F72-44A7-89C5-5595FE6B30EE" => Some("Users"),
+        "031E4825-7B94-4DC3-B131-E946B44C8DD5" => Some("Libraries"),
+        "ED228FDF-9EA8-4870-83B1-96B02CFE0D52" => Some("My Games"),
+        "679F85CB-0220-4080-B29B-5540CC05AAB6" => Some("Quick Access"),
+        "018D5C66-4533-4307-9B53-224DE2ED1FE6" => Some("OneDrive"),
+        "B4BFCC3A-DB2C-424C-B029-7FE99A87C641" => Some("Desktop"),
+        "1CF1260C-4DD0-4EBB-811F-33C572699FDE" => Some("Music"),
+        "3ADD1653-EB32-4CB0-BBD7-DFA0ABB5ACCA" => Some("Pictures"),
+        "A0953C92-50DC-43BF-BE83-3742FED03C9C" => Some("Videos"),
+        "374DE290-123F-4565-9164-39C4925E467B" => Some("Downloads"),
+        _ => None
+    }
+}
+
+// Returns the display name associated with a root folder sort index
+pub fn get_sort_index_name(sort_index: u8) -> Option<&'static str> {
+    match sort_index {
+        0x00 => Some("Internet Explorer"),
+        0x42 => Some("Libraries"),
+        0x44 => Some("Users"),
+        0x48 => Some("My Documents"),
+        0x50 => Some("My Computer"),
+        0x58 => Some("My Network Places"),
+        0x60 => Some("Recycle Bin"),
+        0x68 => Some("Internet Explorer"),
+        0x70 => Some("Control Panel"),
+        0x80 => Some("My Games"),
+        _ => None
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#32-root-folder-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct RootFolderShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub sort_index: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_index_name: Option<&'static str>,
+    pub guid: Guid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl RootFolderShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, sort_index: u8, size: u16) -> Result<RootFolderShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let guid = Guid::new(&mut reader)?;
+
+        // the sort index only hints at the folder type, unknown identifiers
+        // are left without a name
+        let name = get_shell_folder_name(&guid);
+        let sort_index_name = get_sort_index_name(sort_index);
+
+        // size(2), class_type(1), sort_index(1) and guid(16) make up 20 bytes,
+        // anything past that are extension blocks
+        let mut extension_blocks = None;
+        if size > 20 {
+            let mut extension_list = ExtensionList::new(&mut reader, size as u64 - 20)?;
+            extension_list.check_version_offsets(20);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            RootFolderShellItem {
+                _offset: _offset,
+                sort_index: sort_index,
+                sort_index_name: sort_index_name,
+                guid: guid,
+                name: name,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+#[test]
+fn test_root_folder_item() {
+    use std::io::Cursor;
+    let buffer: &[u8] = &[
+        0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D
+    ];
+
+    let root_folder = RootFolderShellItem::new(
+        Cursor::new(buffer), 0x50, 20
+    ).unwrap();
+    assert_eq!(format!("{}",root_folder.guid),"20D04FE0-3AEA-1069-A2D8-08002B30309D");
+    assert_eq!(root_folder.name,Some("My Computer"));
+}
+
+#[test]
+fn test_root_folder_unknown_guid() {
+    use shellitem::{ShellContent};
+    use std::io::Cursor;
+    // An identifier that is not a known shell folder with the My Computer sort index
+    let buffer: &[u8] = &[
+        0x01,0x02,0x03,0x04,0x05,0x06,0x07,0x08,0x09,0x0A,0x0B,0x0C,0x0D,0x0E,0x0F,0x10
+    ];
+
+    let root_folder = RootFolderShellItem::new(
+        Cursor::new(buffer), 0x50, 20
+    ).unwrap();
+    assert_eq!(root_folder.name,None);
+    assert_eq!(root_folder.sort_index_name,Some("My Computer"));
+    assert_eq!(
+        ShellContent::RootFolder(root_folder).get_name(true),
+        Some(String::from("{04030201-0605-0807-090A-0B0C0D0E0F10}"))
+    );
+}
+
+#[test]
+fn test_root_folder_version_offset_mismatch() {
+    use errors::{Anomaly};
+    use extension_blocks::{ExtensionContent};
+    use std::io::Cursor;
+    // My Computer followed by a version 3 Beef0004 block whose version offset
+    // is 16 instead of 20
+    let buffer: &[u8] = &[
+        0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,0x2B,0x30,0x30,0x9D,
+        0x1A,0x00,0x03,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x14,0x00,0x00,0x00,0x41,0x00,0x00,0x00,0x10,0x00
+    ];
+
+    let root_folder = RootFolderShellItem::new(
+        Cursor::new(buffer), 0x50, 46
+    ).unwrap();
+    let extension_blocks = root_folder.extension_blocks.unwrap();
+    match extension_blocks[0].get_content() {
+        Some(&ExtensionContent::FileEntry(ref beef0004)) => {
+            match beef0004.get_anomalies().first() {
+                Some(&Anomaly::VersionOffsetMismatch { expected, found }) => {
+                    assert_eq!(expected,20);
+                    assert_eq!(found,16);
+                },
+                _ => panic!("expected a version offset mismatch")
+            }
+        },
+        _ => panic!("expected a Beef0004 block")
+    }
+}
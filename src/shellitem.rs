@@ -1,19 +1,79 @@
 use byteorder::{ReadBytesExt, LittleEndian};
 use serde::{ser};
-use errors::{ShellItemError};
+use errors::{ShellItemError, ErrorKind, Anomaly};
 use file_entry_shell::{FileEntryShellItem};
+use root_folder_shell::{RootFolderShellItem};
+use volume_shell::{VolumeShellItem};
+use network_shell::{NetworkLocationShellItem};
+use uri_shell::{UriShellItem};
+use control_panel_shell::{ControlPanelShellItem, ControlPanelCategoryShellItem, CONTROL_PANEL_CATEGORY_SIGNATURE};
+use delegate_shell::{DelegateShellItem, UsersPropertyViewShellItem};
+use compressed_folder_shell::{CompressedFolderShellItem};
+use delegate_shell::{DELEGATE_SIGNATURE, USERS_PROPERTY_VIEW_SIGNATURE};
+use registry;
+use serde_json::{Value};
 use utils;
+use utils::{WindowReader};
 use std::io::Read;
 use std::io::{Seek,SeekFrom};
 use std::fmt;
+use std::cmp;
+use std::cell::Cell;
 
 #[derive(Serialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum ShellContent {
     Raw(RawContent),
+    RootFolder(RootFolderShellItem),
+    Volume(VolumeShellItem),
     FileEntry(FileEntryShellItem),
+    NetworkLocation(NetworkLocationShellItem),
+    CompressedFolder(CompressedFolderShellItem),
+    Uri(UriShellItem),
+    ControlPanel(ControlPanelShellItem),
+    ControlPanelCategory(ControlPanelCategoryShellItem),
+    Delegate(DelegateShellItem),
+    UsersPropertyView(UsersPropertyViewShellItem),
+    // Content produced by a parser registered with registry::register_shell_item_parser
+    Custom(Value),
     None
 }
+impl ShellContent {
+    // The name this content contributes to the path of a shell item list
+    pub fn get_name(&self, long_names: bool) -> Option<String> {
+        match *self {
+            ShellContent::RootFolder(ref item) => match item.name {
+                Some(name) => Some(name.to_string()),
+                None => Some(format!("{{{}}}", item.guid))
+            },
+            ShellContent::Volume(ref item) => {
+                if let Some(ref name) = item.name {
+                    // drive letters are stored with a trailing backslash, e.g. "C:\"
+                    if name.ends_with('\\') {
+                        return Some(name[..name.len() - 1].to_string());
+                    }
+                    return Some(name.clone());
+                }
+                match item.guid_name {
+                    Some(name) => Some(name.to_string()),
+                    None => item.guid.as_ref().map(|guid| format!("{{{}}}", guid))
+                }
+            },
+            ShellContent::FileEntry(ref item) => Some(item.get_name(long_names).to_string()),
+            ShellContent::NetworkLocation(ref item) => Some(item.location.clone()),
+            ShellContent::CompressedFolder(ref item) => item.path.clone(),
+            ShellContent::Uri(ref item) => Some(item.uri.clone()),
+            ShellContent::ControlPanel(ref item) => match item.name {
+                Some(name) => Some(name.to_string()),
+                None => Some(format!("{{{}}}", item.guid))
+            },
+            ShellContent::ControlPanelCategory(ref item) => Some(format!("{}", item.category)),
+            ShellContent::Delegate(ref item) => item.inner_item.get_display_name(long_names),
+            ShellContent::UsersPropertyView(ref item) => item.identifier_name.map(|name| name.to_string()),
+            ShellContent::Raw(_) | ShellContent::Custom(_) | ShellContent::None => None
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ClassType(
@@ -56,6 +116,22 @@ impl ser::Serialize for ClassType {
     }
 }
 
+// Several class types are shared by different items which are told apart by a
+// signature embedded in the item. The reader is expected to be positioned after
+// the unknown byte (offset 4) and is left where it was.
+fn peek_signature<Rs: Read+Seek>(mut reader: Rs, size: u16, offset: u16) -> Result<Option<u32>,ShellItemError> {
+    if size < offset + 4 {
+        return Ok(None);
+    }
+
+    let relative_offset = offset as i64 - 4;
+    reader.seek(SeekFrom::Current(relative_offset))?;
+    let signature = utils::peek_u32(&mut reader)?;
+    reader.seek(SeekFrom::Current(-relative_offset))?;
+
+    Ok(Some(signature))
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct ShellData {
     #[serde(skip_serializing)]
@@ -70,28 +146,35 @@ impl ShellData {
         let class_type = ClassType(reader.read_u8()?);
         let unknown = reader.read_u8()?;
 
-        let mut content = ShellContent::None;
-
-        match class_type.get_type() {
-            0x30...0x3F => {
-                content = ShellContent::FileEntry(
-                    FileEntryShellItem::new(
-                        &mut reader,
-                        &class_type
-                    )?
-                );
-            },
-            _ => {
-                // subtract 4 from size to account for size(2), class_type(1), and unknown(1)
-                let buff_size = size - 4;
-                let mut buffer = vec![0; buff_size as usize];
-                reader.read_exact(&mut buffer)?;
+        // registered parsers take precedence over the built in ones
+        let content = match registry::parse_shell_item(&mut reader, class_type.get_type(), unknown, size)? {
+            Some(value) => ShellContent::Custom(value),
+            None => ShellData::read_content(&mut reader, &class_type, unknown, size)?
+        };
 
-                content = ShellContent::Raw(
-                    RawContent(buffer)
-                );
+        Ok(
+            ShellData {
+                _offset: _offset,
+                class_type: class_type,
+                unknown: unknown,
+                content: content
             }
-        }
+        )
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self._offset
+    }
+
+    // Keeps the item data as raw content, used when the item could not be parsed
+    // within its size
+    fn new_raw<Rs: Read+Seek>(mut reader: Rs, size: u16) -> Result<ShellData,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let class_type = ClassType(reader.read_u8()?);
+        let unknown = reader.read_u8()?;
+        let content = ShellContent::Raw(
+            RawContent::new(&mut reader, size)?
+        );
 
         Ok(
             ShellData {
@@ -102,6 +185,130 @@ impl ShellData {
             }
         )
     }
+
+    fn read_content<Rs: Read+Seek>(mut reader: Rs, class_type: &ClassType, unknown: u8, size: u16) -> Result<ShellContent,ShellItemError> {
+        let content = match class_type.get_type() {
+            0x1F => {
+                ShellContent::RootFolder(
+                    RootFolderShellItem::new(
+                        &mut reader,
+                        unknown,
+                        size
+                    )?
+                )
+            },
+            0x20...0x2F => {
+                // volume names start at the byte read as unknown
+                reader.seek(SeekFrom::Current(-1))?;
+                ShellContent::Volume(
+                    VolumeShellItem::new(
+                        &mut reader,
+                        class_type,
+                        size
+                    )?
+                )
+            },
+            0x30...0x3F => {
+                ShellContent::FileEntry(
+                    FileEntryShellItem::new(
+                        &mut reader,
+                        class_type,
+                        size
+                    )?
+                )
+            },
+            0x40...0x4F => {
+                ShellContent::NetworkLocation(
+                    NetworkLocationShellItem::new(
+                        &mut reader,
+                        class_type,
+                        size
+                    )?
+                )
+            },
+            0x52 => {
+                ShellContent::CompressedFolder(
+                    CompressedFolderShellItem::new(
+                        &mut reader,
+                        unknown,
+                        size
+                    )?
+                )
+            },
+            0x61 => {
+                ShellContent::Uri(
+                    UriShellItem::new(
+                        &mut reader,
+                        unknown,
+                        size
+                    )?
+                )
+            },
+            0x71 => {
+                ShellContent::ControlPanel(
+                    ControlPanelShellItem::new(
+                        &mut reader,
+                        size
+                    )?
+                )
+            },
+            0x74 => {
+                match peek_signature(&mut reader, size, 6)? {
+                    Some(DELEGATE_SIGNATURE) => {
+                        ShellContent::Delegate(
+                            DelegateShellItem::new(
+                                &mut reader,
+                                size
+                            )?
+                        )
+                    },
+                    _ => ShellContent::Raw(
+                        RawContent::new(&mut reader, size)?
+                    )
+                }
+            },
+            0x00 => {
+                // portable device (MTP) items use their own layouts and are kept raw
+                match peek_signature(&mut reader, size, 6)? {
+                    Some(USERS_PROPERTY_VIEW_SIGNATURE) => {
+                        ShellContent::UsersPropertyView(
+                            UsersPropertyViewShellItem::new(
+                                &mut reader, size
+                            )?
+                        )
+                    },
+                    _ => ShellContent::Raw(
+                        RawContent::new(&mut reader, size)?
+                    )
+                }
+            },
+            0x01 => {
+                match peek_signature(&mut reader, size, 4)? {
+                    Some(CONTROL_PANEL_CATEGORY_SIGNATURE) => {
+                        ShellContent::ControlPanelCategory(
+                            ControlPanelCategoryShellItem::new(
+                                &mut reader,
+                                size
+                            )?
+                        )
+                    },
+                    _ => ShellContent::Raw(
+                        RawContent::new(&mut reader, size)?
+                    )
+                }
+            },
+            _ => {
+                ShellContent::Raw(
+                    RawContent::new(
+                        &mut reader,
+                        size
+                    )?
+                )
+            }
+        };
+
+        Ok(content)
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -110,28 +317,121 @@ pub struct ShellItem {
     _offset: u64,
     #[serde(skip_serializing)]
     pub size: u16,
-    pub data: Option<ShellData>
+    pub data: Option<ShellData>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    anomalies: Vec<Anomaly>
+}
+// Shell items contain other shell items through delegate items and extension
+// blocks holding shell lists. Items nested deeper than this fail to parse instead
+// of recursing until the stack overflows.
+pub const MAX_NESTING_DEPTH: usize = 16;
+
+thread_local! {
+    static NESTING_DEPTH: Cell<usize> = Cell::new(0);
+}
+
+// Counts a level of nesting for as long as it is alive
+struct NestingGuard;
+impl NestingGuard {
+    fn enter(offset: u64) -> Result<NestingGuard,ShellItemError> {
+        let depth = NESTING_DEPTH.with(|depth| depth.get());
+        if depth >= MAX_NESTING_DEPTH {
+            return Err(
+                ShellItemError::max_depth_exceeded(
+                    format!("Shell item at offset {} is nested more than {} levels deep", offset, MAX_NESTING_DEPTH)
+                )
+            );
+        }
+
+        NESTING_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Ok(NestingGuard)
+    }
+}
+impl Drop for NestingGuard {
+    fn drop(&mut self) {
+        NESTING_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
 }
+
 impl ShellItem {
+    // The item is parsed from a window limited to its size so a malformed item
+    // can not read into the items that follow it. The reader is always left at
+    // the end of the item.
     pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<ShellItem,ShellItemError> {
         let _offset = reader.seek(SeekFrom::Current(0))?;
         let size = reader.read_u16::<LittleEndian>()?;
 
         let mut data: Option<ShellData> = None;
+        let mut anomalies = Vec::new();
         if size > 0 {
-            data = Some(
-                ShellData::new(
-                    &mut reader,
-                    size
-                )?
-            );
+            let _depth = NestingGuard::enter(_offset)?;
+
+            // subtract 2 from size to account for size(2)
+            let mut window = WindowReader::new(&mut reader, size.saturating_sub(2) as u64)?;
+
+            data = match ShellData::new(&mut window, size) {
+                Ok(shell_data) => Some(shell_data),
+                Err(error) => {
+                    // content that fails to parse is kept as raw data so one bad
+                    // item does not fail the list it belongs to
+                    if error.kind == ErrorKind::IoError && window.get_overrun() > 0 {
+                        warn!(
+                            "Shell item at offset {} reads {} bytes past its size of {}: {}",
+                            _offset, window.get_overrun(), size, error.message
+                        );
+                        anomalies.push(
+                            Anomaly::Overrun {
+                                offset: window.get_end(),
+                                size: window.get_overrun()
+                            }
+                        );
+                    } else {
+                        warn!(
+                            "Unable to parse shell item at offset {}: {}",
+                            _offset, error.message
+                        );
+                        anomalies.push(
+                            Anomaly::ParseError {
+                                offset: _offset + 2,
+                                message: error.message
+                            }
+                        );
+                    }
+
+                    // items too small for class_type(1) and unknown(1) have no data.
+                    // Reading the raw data only fails if the item is cut off by
+                    // the end of the data.
+                    window.seek(SeekFrom::Start(_offset + 2))?;
+                    if size >= 4 {
+                        Some(
+                            ShellData::new_raw(&mut window, size)?
+                        )
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            let leftover = window.remaining()?;
+            if leftover > 0 {
+                anomalies.push(
+                    Anomaly::LeftoverBytes {
+                        offset: window.get_end() - leftover,
+                        size: leftover
+                    }
+                );
+            }
         }
 
+        // a null item still consumes its size field
+        reader.seek(SeekFrom::Start(_offset + cmp::max(size, 2) as u64))?;
+
         Ok(
             ShellItem {
                 _offset: _offset,
                 size: size,
-                data: data
+                data: data,
+                anomalies: anomalies
             }
         )
     }
@@ -139,6 +439,22 @@ impl ShellItem {
     pub fn get_size(&self) -> u16 {
         self.size
     }
+
+    pub fn get_offset(&self) -> u64 {
+        self._offset
+    }
+
+    pub fn get_anomalies(&self) -> &Vec<Anomaly> {
+        &self.anomalies
+    }
+
+    // The name of the item as shown by Explorer, None for items without a name
+    pub fn get_display_name(&self, long_names: bool) -> Option<String> {
+        match self.data {
+            Some(ref data) => data.content.get_name(long_names),
+            None => None
+        }
+    }
 }
 
 // Raw Content will be used for unhandled shell item data
@@ -146,6 +462,18 @@ impl ShellItem {
 pub struct RawContent(
     pub Vec<u8>
 );
+impl RawContent {
+    pub fn new<R: Read>(mut reader: R, size: u16) -> Result<RawContent,ShellItemError> {
+        // subtract 4 from size to account for size(2), class_type(1), and unknown(1)
+        let buff_size = size.saturating_sub(4);
+        let mut buffer = vec![0; buff_size as usize];
+        reader.read_exact(&mut buffer)?;
+
+        Ok(
+            RawContent(buffer)
+        )
+    }
+}
 impl fmt::Debug for RawContent {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", utils::to_hex_string(&self.0))
@@ -161,3 +489,116 @@ impl ser::Serialize for RawContent {
         )
     }
 }
+
+#[test]
+fn test_shell_item_overrun() {
+    use std::io::Cursor;
+    // A root folder item that is too small for its guid followed by a null item
+    let buffer: &[u8] = &[
+        0x0A,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x00,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let shell_item = ShellItem::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),10);
+    match shell_item.data.unwrap().content {
+        ShellContent::Raw(ref raw) => assert_eq!(raw.0.len(),6),
+        _ => panic!("expected raw content")
+    }
+    match shell_item.anomalies.first() {
+        Some(&Anomaly::Overrun { offset, .. }) => assert_eq!(offset,10),
+        _ => panic!("expected an overrun anomaly")
+    }
+}
+
+#[test]
+fn test_shell_item_nesting_depth() {
+    use std::io::Cursor;
+    // Wraps a My Computer root folder item in `levels` delegate items
+    fn nested_delegates(levels: usize) -> Vec<u8> {
+        let mut buffer: Vec<u8> = vec![
+            0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+            0x2B,0x30,0x30,0x9D
+        ];
+        for _ in 0..levels {
+            let size = (buffer.len() + 42) as u16;
+            let mut item = vec![
+                size as u8,(size >> 8) as u8,0x74,0x00,0x10,0x00,0x43,0x46,0x53,0x46
+            ];
+            item.extend_from_slice(&buffer);
+            item.extend_from_slice(&[0; 32]);
+            buffer = item;
+        }
+        buffer
+    }
+
+    // Returns the innermost delegate item of `levels` nested delegate items
+    fn innermost_delegate(mut shell_item: &ShellItem, levels: usize) -> &ShellItem {
+        for _ in 1..levels {
+            shell_item = match shell_item.data.as_ref().unwrap().content {
+                ShellContent::Delegate(ref delegate) => &*delegate.inner_item,
+                _ => panic!("expected a delegate item")
+            };
+        }
+        shell_item
+    }
+
+    let shell_item = ShellItem::new(Cursor::new(nested_delegates(MAX_NESTING_DEPTH - 1))).unwrap();
+    let innermost = innermost_delegate(&shell_item, MAX_NESTING_DEPTH - 1);
+    assert!(innermost.anomalies.is_empty());
+    match innermost.data.as_ref().unwrap().content {
+        ShellContent::Delegate(ref delegate) => assert_eq!(delegate.inner_item.get_display_name(true),Some(String::from("My Computer"))),
+        _ => panic!("expected a delegate item")
+    }
+
+    // the item past the limit fails, which keeps the delegate holding it raw
+    let shell_item = ShellItem::new(Cursor::new(nested_delegates(MAX_NESTING_DEPTH))).unwrap();
+    let innermost = innermost_delegate(&shell_item, MAX_NESTING_DEPTH);
+    match innermost.data.as_ref().unwrap().content {
+        ShellContent::Raw(_) => {},
+        _ => panic!("expected raw content")
+    }
+    match innermost.anomalies.first() {
+        Some(&Anomaly::ParseError { ref message, .. }) => assert!(message.contains("nested more than")),
+        _ => panic!("expected a parse error anomaly")
+    }
+}
+
+#[test]
+fn test_shell_item_parse_error() {
+    use std::io::Cursor;
+    // A uri item whose data size exceeds the item followed by a null item
+    let buffer: &[u8] = &[
+        0x0A,0x00,0x61,0x00,0xFF,0x00,0x00,0x00,0x00,0x00,0x00,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let shell_item = ShellItem::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),10);
+    match shell_item.data.unwrap().content {
+        ShellContent::Raw(ref raw) => assert_eq!(raw.0,vec![0xFF,0x00,0x00,0x00,0x00,0x00]),
+        _ => panic!("expected raw content")
+    }
+    match shell_item.anomalies.first() {
+        Some(&Anomaly::ParseError { offset, .. }) => assert_eq!(offset,2),
+        _ => panic!("expected a parse error anomaly")
+    }
+}
+
+#[test]
+fn test_shell_item_too_small() {
+    use std::io::Cursor;
+    // An item too small for its class type and unknown byte followed by a null item
+    let buffer: &[u8] = &[
+        0x03,0x00,0x1F,0x00,0x00
+    ];
+
+    let mut reader = Cursor::new(buffer);
+    let shell_item = ShellItem::new(&mut reader).unwrap();
+    assert_eq!(reader.position(),3);
+    assert!(shell_item.data.is_none());
+    match shell_item.anomalies.first() {
+        Some(&Anomaly::Overrun { offset, .. }) => assert_eq!(offset,3),
+        _ => panic!("expected an overrun anomaly")
+    }
+}
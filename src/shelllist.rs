@@ -1,18 +1,142 @@
-use shellitem::{ShellItem};
+use shellitem::{ShellItem, RawContent};
 use errors::{ShellItemError};
+use utils::{WindowReader};
+use byteorder::{ReadBytesExt, LittleEndian};
 use std::io::Read;
-use std::io::Seek;
+use std::io::{Seek,SeekFrom};
+use std::ops::Index;
+use std::slice;
+use std::vec;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseMode {
+    // Any item that fails to parse fails the whole list
+    Strict,
+    // Items that fail to parse are kept as FailedShellItem and parsing continues
+    // with the next item
+    Lenient
+}
+
+// An item that could not be parsed in lenient mode
+#[derive(Serialize, Clone, Debug)]
+pub struct FailedShellItem {
+    pub offset: u64,
+    pub size: u16,
+    pub raw: RawContent,
+    pub error: ShellItemError
+}
+impl FailedShellItem {
+    // Reads the size and raw bytes of the item at `offset`. Only the bytes that
+    // are available are kept if the item is truncated.
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, offset: u64, error: ShellItemError) -> Result<FailedShellItem,ShellItemError> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let size = match reader.read_u16::<LittleEndian>() {
+            Ok(size) => size,
+            Err(_) => 0
+        };
+
+        let mut raw = Vec::new();
+        reader.seek(SeekFrom::Start(offset))?;
+        WindowReader::new(&mut reader, size as u64)?.read_to_end(&mut raw)?;
+
+        Ok(
+            FailedShellItem {
+                offset: offset,
+                size: size,
+                raw: RawContent(raw),
+                error: error
+            }
+        )
+    }
+}
+
+// An entry of a shell list. Failed items keep their position in the list.
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum ShellListEntry {
+    Parsed(ShellItem),
+    Failed(FailedShellItem)
+}
+impl ShellListEntry {
+    // None for items that failed to parse
+    pub fn get_item(&self) -> Option<&ShellItem> {
+        match *self {
+            ShellListEntry::Parsed(ref item) => Some(item),
+            ShellListEntry::Failed(_) => None
+        }
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        match *self {
+            ShellListEntry::Parsed(ref item) => item.get_offset(),
+            ShellListEntry::Failed(ref failed_item) => failed_item.offset
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        match *self {
+            ShellListEntry::Parsed(_) => false,
+            ShellListEntry::Failed(_) => true
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PathOptions {
+    pub separator: String,
+    // Use the long names of file entries instead of their 8.3 names
+    pub long_names: bool,
+    // Written in place of items that failed to parse so the path keeps the
+    // position of the missing segment
+    pub failed_segment: String
+}
+impl Default for PathOptions {
+    fn default() -> PathOptions {
+        PathOptions {
+            separator: String::from("\\"),
+            long_names: true,
+            failed_segment: String::from("<unparsed>")
+        }
+    }
+}
 
 #[derive(Serialize, Clone, Debug)]
 pub struct ShellList(
-    Vec<ShellItem>
+    Vec<ShellListEntry>
 );
 
 impl ShellList {
-    pub fn new<Rs: Read+Seek>(mut reader: Rs) -> Result<ShellList, ShellItemError> {
-        let mut shell_items: Vec<ShellItem> = Vec::new();
+    pub fn new<Rs: Read+Seek>(reader: Rs) -> Result<ShellList, ShellItemError> {
+        ShellList::new_with_mode(reader, ParseMode::Strict)
+    }
+
+    pub fn new_with_mode<Rs: Read+Seek>(mut reader: Rs, mode: ParseMode) -> Result<ShellList, ShellItemError> {
+        let mut entries: Vec<ShellListEntry> = Vec::new();
         loop {
-            let shell_item = ShellItem::new(&mut reader)?;
+            let item_offset = reader.seek(SeekFrom::Current(0))?;
+            let shell_item = match ShellItem::new(&mut reader) {
+                Ok(shell_item) => shell_item,
+                Err(error) => {
+                    if mode == ParseMode::Strict {
+                        return Err(error);
+                    }
+
+                    warn!("Unable to parse shell item at offset {}: {}", item_offset, error.message);
+                    let failed_item = FailedShellItem::new(&mut reader, item_offset, error)?;
+                    let size = failed_item.size;
+                    let truncated = (failed_item.raw.0.len() as u64) < size as u64;
+                    entries.push(ShellListEntry::Failed(failed_item));
+
+                    // the next item can not be located without a usable size and
+                    // nothing follows an item that is cut off by the end of the data
+                    if size < 2 || truncated {
+                        break
+                    }
+
+                    reader.seek(SeekFrom::Start(item_offset + size as u64))?;
+                    continue
+                }
+            };
             debug!("{:?}",shell_item);
             let size = shell_item.get_size();
 
@@ -21,11 +145,145 @@ impl ShellList {
                 break
             }
 
-            shell_items.push(shell_item);
+            entries.push(ShellListEntry::Parsed(shell_item));
         }
 
         Ok(
-            ShellList(shell_items)
+            ShellList(entries)
         )
     }
+
+    pub fn get_failed_items(&self) -> Vec<&FailedShellItem> {
+        self.0.iter()
+            .filter_map(|entry| match *entry {
+                ShellListEntry::Failed(ref failed_item) => Some(failed_item),
+                ShellListEntry::Parsed(_) => None
+            })
+            .collect()
+    }
+
+    pub fn items(&self) -> &[ShellListEntry] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> slice::Iter<ShellListEntry> {
+        self.0.iter()
+    }
+
+    // Number of entries, including items that failed to parse
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Joins the display names of the items, e.g.
+    // "My Computer\C:\Program Files\Microsoft Office\Office15\MSACCESS.EXE".
+    // Parsed items without a name are skipped, items that failed to parse are
+    // written as the failed segment of the options.
+    pub fn to_path(&self) -> String {
+        self.to_path_with_options(&PathOptions::default())
+    }
+
+    pub fn to_path_with_options(&self, options: &PathOptions) -> String {
+        let names: Vec<String> = self.0.iter()
+            .filter_map(|entry| match *entry {
+                ShellListEntry::Parsed(ref item) => item.get_display_name(options.long_names),
+                ShellListEntry::Failed(_) => Some(options.failed_segment.clone())
+            })
+            .collect();
+        names.join(&options.separator)
+    }
+}
+impl Index<usize> for ShellList {
+    type Output = ShellListEntry;
+
+    fn index(&self, index: usize) -> &ShellListEntry {
+        &self.0[index]
+    }
+}
+impl IntoIterator for ShellList {
+    type Item = ShellListEntry;
+    type IntoIter = vec::IntoIter<ShellListEntry>;
+
+    fn into_iter(self) -> vec::IntoIter<ShellListEntry> {
+        self.0.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a ShellList {
+    type Item = &'a ShellListEntry;
+    type IntoIter = slice::Iter<'a, ShellListEntry>;
+
+    fn into_iter(self) -> slice::Iter<'a, ShellListEntry> {
+        self.0.iter()
+    }
+}
+
+#[test]
+fn test_lenient_shell_list() {
+    use std::io::Cursor;
+    // A root folder item followed by a file entry item that is cut off before
+    // the end of its size
+    let buffer: &[u8] = &[
+        0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+        0x2B,0x30,0x30,0x9D,
+        0x20,0x00,0x31,0x00,0x00,0x00
+    ];
+
+    assert!(ShellList::new(Cursor::new(buffer)).is_err());
+
+    let shell_list = ShellList::new_with_mode(Cursor::new(buffer), ParseMode::Lenient).unwrap();
+    assert_eq!(shell_list.len(),2);
+    assert_eq!(shell_list[0].get_offset(),0);
+    match shell_list[1] {
+        ShellListEntry::Failed(ref failed_item) => {
+            assert_eq!(failed_item.offset,20);
+            assert_eq!(failed_item.raw.0.len(),6);
+        },
+        _ => panic!("expected the second item to have failed")
+    }
+    assert_eq!(shell_list.to_path(),"My Computer\\<unparsed>");
+}
+
+#[test]
+fn test_shell_list_to_path() {
+    use std::io::Cursor;
+    let buffer: &[u8] = &[
+        0x14,0x00,0x1F,0x50,0xE0,0x4F,0xD0,0x20,0xEA,0x3A,0x69,0x10,0xA2,0xD8,0x08,0x00,
+        0x2B,0x30,0x30,0x9D,0x19,0x00,0x2F,0x43,0x3A,0x5C,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x64,0x00,0x31,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x50,0x72,0x6F,0x67,0x72,
+        0x61,0x6D,0x20,0x46,0x69,0x6C,0x65,0x73,0x00,0x48,0x00,0x08,0x00,0x04,0x00,0xEF,
+        0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x50,0x00,0x72,0x00,0x6F,0x00,0x67,0x00,0x72,0x00,0x61,0x00,0x6D,
+        0x00,0x20,0x00,0x46,0x00,0x69,0x00,0x6C,0x00,0x65,0x00,0x73,0x00,0x00,0x00,0x1C,
+        0x00,0x6E,0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x4D,
+        0x69,0x63,0x72,0x6F,0x73,0x6F,0x66,0x74,0x20,0x4F,0x66,0x66,0x69,0x63,0x65,0x00,
+        0x00,0x4E,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x4D,0x00,0x69,0x00,0x63,
+        0x00,0x72,0x00,0x6F,0x00,0x73,0x00,0x6F,0x00,0x66,0x00,0x74,0x00,0x20,0x00,0x4F,
+        0x00,0x66,0x00,0x66,0x00,0x69,0x00,0x63,0x00,0x65,0x00,0x00,0x00,0x20,0x00,0x56,
+        0x00,0x31,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x10,0x00,0x4F,0x66,0x66,
+        0x69,0x63,0x65,0x31,0x35,0x00,0x00,0x3E,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x4F,0x00,0x66,0x00,0x66,0x00,0x69,0x00,0x63,0x00,0x65,0x00,0x31,0x00,0x35,
+        0x00,0x00,0x00,0x18,0x00,0x62,0x00,0x32,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x4D,0x53,0x41,0x43,0x43,0x45,0x53,0x53,0x2E,0x45,0x58,0x45,0x00,
+        0x00,0x46,0x00,0x08,0x00,0x04,0x00,0xEF,0xBE,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x2A,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x4D,0x00,0x53,0x00,0x41,
+        0x00,0x43,0x00,0x43,0x00,0x45,0x00,0x53,0x00,0x53,0x00,0x2E,0x00,0x45,0x00,0x58,
+        0x00,0x45,0x00,0x00,0x00,0x1C,0x00,0x00,0x00
+    ];
+
+    let shell_list = ShellList::new(Cursor::new(buffer)).unwrap();
+    assert_eq!(
+        shell_list.to_path(),
+        "My Computer\\C:\\Program Files\\Microsoft Office\\Office15\\MSACCESS.EXE"
+    );
 }
@@ -0,0 +1,183 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+use rwinstructs::timestamp::{WinTimestamp};
+use errors::{ShellItemError};
+use extension_blocks::{ExtensionList};
+use file_entry_shell::{FLAGS_AS_INT};
+use utils;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::io::{Error,ErrorKind};
+use std::fmt;
+use serde::{ser};
+
+bitflags! {
+    pub struct UriFlags: u8 {
+        const UNKNOWN1             = 0x01;
+        const UNKNOWN2             = 0x02;
+        const UNKNOWN4             = 0x04;
+        const UNKNOWN8             = 0x08;
+        const UNKNOWN10            = 0x10;
+        const UNKNOWN20            = 0x20;
+        const UNKNOWN40            = 0x40;
+        const HAS_UNICODE_STRINGS  = 0x80;
+    }
+}
+impl fmt::Display for UriFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.bits())
+    }
+}
+impl ser::Serialize for UriFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        if unsafe{FLAGS_AS_INT} {
+            serializer.serialize_u8(self.bits())
+        } else {
+            serializer.serialize_str(&format!("{:?}", self))
+        }
+    }
+}
+
+// Offset of the host name relative to the start of the uri data
+const FTP_STRINGS_OFFSET: u64 = 36;
+
+fn read_sized_string(reader: &mut Cursor<Vec<u8>>, unicode: bool) -> Result<String,ShellItemError> {
+    // Strings in the uri data are prefixed with their size in bytes
+    let string_size = reader.read_u32::<LittleEndian>()? as u64;
+    let remaining = (reader.get_ref().len() as u64).saturating_sub(reader.position());
+    if string_size > remaining {
+        return Err(
+            ShellItemError::from(
+                Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("String size {} exceeds the {} bytes left in the uri data", string_size, remaining)
+                )
+            )
+        );
+    }
+
+    let mut buffer = vec![0; string_size as usize];
+    reader.read_exact(&mut buffer)?;
+
+    if unicode {
+        utils::decode_utf16le(&buffer)
+    } else {
+        utils::decode_ascii(&buffer)
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#39-uri-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct UriShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub flags: UriFlags,
+    pub data_size: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<WinTimestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl UriShellItem {
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, flags: u8, size: u16) -> Result<UriShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let flags = UriFlags::from_bits_truncate(flags);
+        let unicode = flags.contains(HAS_UNICODE_STRINGS);
+
+        // subtract 4 from size to account for size(2), class_type(1), and flags(1)
+        let mut buffer = vec![0; size.saturating_sub(4) as usize];
+        reader.read_exact(&mut buffer)?;
+        let mut content_reader = Cursor::new(buffer);
+
+        let data_size = content_reader.read_u16::<LittleEndian>()?;
+        let mut timestamp = None;
+        let mut host = None;
+        let mut user_name = None;
+
+        if data_size > 0 {
+            let mut data_buffer = vec![0; data_size as usize];
+            content_reader.read_exact(&mut data_buffer)?;
+            let mut data_reader = Cursor::new(data_buffer);
+
+            if data_size >= 16 {
+                let _unknown1 = data_reader.read_u32::<LittleEndian>()?;
+                let _unknown2 = data_reader.read_u32::<LittleEndian>()?;
+                timestamp = Some(
+                    WinTimestamp(data_reader.read_u64::<LittleEndian>()?)
+                );
+            }
+
+            // The host and user name are only present in ftp uris and follow
+            // a 4 byte unknown, 12 bytes of empty values and a 4 byte unknown
+            if data_size as u64 > FTP_STRINGS_OFFSET {
+                data_reader.seek(SeekFrom::Start(FTP_STRINGS_OFFSET))?;
+                host = Some(
+                    read_sized_string(&mut data_reader, unicode)?
+                );
+                user_name = Some(
+                    read_sized_string(&mut data_reader, unicode)?
+                );
+                // the password string follows the user name and is left alone
+            }
+        }
+
+        let uri = if unicode {
+            utils::read_string_u16_till_null(&mut content_reader)?
+        } else {
+            utils::read_string_u8_till_null(&mut content_reader)?
+        };
+
+        // size(2), version(2) and signature(4) is the smallest possible block
+        let mut extension_blocks = None;
+        let remaining = content_reader.get_ref().len() as u64 - content_reader.position();
+        if remaining >= 8 {
+            // the content starts 4 bytes into the item
+            let list_offset = content_reader.position() + 4;
+            let mut extension_list = ExtensionList::new(&mut content_reader, remaining)?;
+            extension_list.check_version_offsets(list_offset);
+            extension_blocks = Some(extension_list);
+        }
+
+        Ok(
+            UriShellItem {
+                _offset: _offset,
+                flags: flags,
+                data_size: data_size,
+                timestamp: timestamp,
+                host: host,
+                user_name: user_name,
+                uri: uri,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+#[test]
+fn test_uri_item() {
+    use std::io::Cursor;
+    // Item data following the flags: the uri data of an ftp uri with the host
+    // "srv" and user name "bob", then the uri
+    let buffer: &[u8] = &[
+        0x38,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0xF6,0xE5,0xD4,0xC3,0xB2,0xA1,
+        0xD0,0x01,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00,0x04,0x00,0x00,0x00,0x73,0x72,0x76,0x00,0x04,0x00,
+        0x00,0x00,0x62,0x6F,0x62,0x00,0x00,0x00,0x00,0x00,0x66,0x74,0x70,0x3A,0x2F,0x2F,
+        0x73,0x72,0x76,0x00
+    ];
+
+    let uri_item = UriShellItem::new(Cursor::new(buffer), 0x01, 72).unwrap();
+    assert_eq!(uri_item.data_size,56);
+    assert_eq!(uri_item.timestamp.unwrap().0,0x01D0A1B2C3D4E5F6);
+    assert_eq!(uri_item.host,Some(String::from("srv")));
+    assert_eq!(uri_item.user_name,Some(String::from("bob")));
+    assert_eq!(uri_item.uri,"ftp://srv");
+    assert!(uri_item.extension_blocks.is_none());
+}
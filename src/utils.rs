@@ -3,7 +3,10 @@ use encoding::all::UTF_16LE;
 use encoding::{Encoding, DecoderTrap};
 use errors::{ShellItemError};
 use std::io::Read;
-use std::io::Error;
+use std::io::{Seek,SeekFrom};
+use std::io::{Error,ErrorKind};
+use std::io;
+use std::cmp;
 use std::slice;
 
 pub fn to_hex_string(bytes: &Vec<u8>) -> String {
@@ -25,6 +28,15 @@ pub fn read_string_u8_till_null<R: Read>(mut reader: R) -> Result<String,ShellIt
     )
 }
 
+pub fn read_string_u8_till_null_lossy<R: Read>(mut reader: R) -> Result<String,ShellItemError> {
+    // Reads into a string till a null char is reached, replacing bytes that are
+    // not valid utf8, e.g. codepage characters, instead of failing
+    let utf8_buffer = get_u8_vec(&mut reader)?;
+    Ok(
+        String::from_utf8_lossy(&utf8_buffer).into_owned()
+    )
+}
+
 pub fn read_string_u16_till_null<R: Read>(mut reader: R) -> Result<String,ShellItemError> {
     // Reads into a string till a null char is reached
     let utf16_buffer = get_u8_vec_utf16(&mut reader)?;
@@ -87,3 +99,128 @@ pub fn get_u16_vec<R: Read>(mut reader: R) -> Result<Vec<u16>,Error> {
 
     Ok(string_vec)
 }
+
+pub fn decode_utf16le(buffer: &[u8]) -> Result<String,ShellItemError> {
+    // Decodes a utf16le buffer, stopping at the first null char if one exists
+    let end = match buffer.chunks(2).position(|c| c.len() == 2 && c[0] == 0x00 && c[1] == 0x00) {
+        Some(index) => index * 2,
+        None => buffer.len() - (buffer.len() % 2)
+    };
+
+    match UTF_16LE.decode(&buffer[..end],DecoderTrap::Ignore) {
+        Ok(utf16) => Ok(utf16),
+        Err(error) => Err(
+            ShellItemError::utf16_decode_error(
+                format!("Error decoding utf16 string. [{}]",error)
+            )
+        )
+    }
+}
+
+pub fn decode_ascii(buffer: &[u8]) -> Result<String,ShellItemError> {
+    // Decodes a single byte string, stopping at the first null char if one exists
+    let string_buffer: Vec<u8> = buffer.iter()
+        .take_while(|b| **b != 0x00)
+        .cloned()
+        .collect();
+    Ok(
+        String::from_utf8(string_buffer)?
+    )
+}
+
+// Object safe combination of Read and Seek. Parsers that recurse into nested items
+// pass the reader as &mut dyn ReadSeek so each level does not add another layer of
+// references to the reader type.
+pub trait ReadSeek: Read + Seek {}
+impl<T: Read + Seek> ReadSeek for T {}
+
+pub fn peek_u32<Rs: Read+Seek>(mut reader: Rs) -> Result<u32,ShellItemError> {
+    // Reads a u32 without moving the reader position
+    let value = reader.read_u32::<LittleEndian>()?;
+    reader.seek(SeekFrom::Current(-4))?;
+    Ok(value)
+}
+
+// Limits reads of the inner reader to the window [start, start + size) where start
+// is the position of the inner reader on creation. Positions returned by seek stay
+// those of the inner reader so offsets recorded while parsing a window match the
+// offsets in the outer stream.
+pub struct WindowReader<R> {
+    inner: R,
+    start: u64,
+    end: u64,
+    overrun: u64
+}
+impl<R: Read+Seek> WindowReader<R> {
+    pub fn new(mut inner: R, size: u64) -> Result<WindowReader<R>,Error> {
+        let start = inner.seek(SeekFrom::Current(0))?;
+        Ok(
+            WindowReader {
+                inner: inner,
+                start: start,
+                end: start + size,
+                overrun: 0
+            }
+        )
+    }
+
+    pub fn get_start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn get_end(&self) -> u64 {
+        self.end
+    }
+
+    // Number of bytes between the current position and the end of the window
+    pub fn remaining(&mut self) -> Result<u64,Error> {
+        let position = self.inner.seek(SeekFrom::Current(0))?;
+        Ok(self.end.saturating_sub(position))
+    }
+
+    // How far past the end of the window reads have tried to go
+    pub fn get_overrun(&self) -> u64 {
+        self.overrun
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+impl<R: Read+Seek> Read for WindowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.inner.seek(SeekFrom::Current(0))?;
+        let requested_end = position + buf.len() as u64;
+        if requested_end > self.end {
+            self.overrun = cmp::max(self.overrun, requested_end - self.end);
+        }
+
+        let available = self.end.saturating_sub(position);
+        let read_size = cmp::min(buf.len() as u64, available) as usize;
+        if read_size == 0 {
+            return Ok(0);
+        }
+
+        self.inner.read(&mut buf[..read_size])
+    }
+}
+impl<R: Read+Seek> Seek for WindowReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.inner.seek(SeekFrom::Current(0))? as i64 + offset,
+            SeekFrom::End(offset) => self.end as i64 + offset
+        };
+
+        if target < self.start as i64 {
+            return Err(
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Seek to {} is before the window start {}", target, self.start)
+                )
+            );
+        }
+
+        self.inner.seek(SeekFrom::Start(target as u64))
+    }
+}
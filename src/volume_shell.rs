@@ -0,0 +1,126 @@
+use byteorder::{ReadBytesExt};
+use errors::{ShellItemError};
+use guid::{Guid};
+use root_folder_shell;
+use extension_blocks::{ExtensionList};
+use file_entry_shell::{FLAGS_AS_INT};
+use shellitem::{ClassType};
+use utils;
+use std::io::Read;
+use std::io::{Seek,SeekFrom};
+use std::fmt;
+use serde::{ser};
+
+bitflags! {
+    pub struct VolumeItemFlags: u8 {
+        const HAS_NAME             = 0x01;
+        const UNKNOWN2             = 0x02;
+        const UNKNOWN4             = 0x04;
+        const REMOVABLE_MEDIA      = 0x08;
+    }
+}
+impl fmt::Display for VolumeItemFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.bits())
+    }
+}
+impl ser::Serialize for VolumeItemFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        if unsafe{FLAGS_AS_INT} {
+            serializer.serialize_u8(self.bits())
+        } else {
+            serializer.serialize_str(&format!("{:?}", self))
+        }
+    }
+}
+
+//https://github.com/libyal/libfwsi/blob/master/documentation/Windows%20Shell%20Item%20format.asciidoc#33-volume-shell-item
+#[derive(Serialize, Clone, Debug)]
+pub struct VolumeShellItem {
+    #[serde(skip_serializing)]
+    _offset: u64,
+    pub sub_flags: VolumeItemFlags,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_index: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guid: Option<Guid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guid_name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_blocks: Option<ExtensionList>
+}
+impl VolumeShellItem {
+    // The reader must be positioned directly after the class type as the volume
+    // name is stored at the offset other items use for the unknown byte.
+    pub fn new<Rs: Read+Seek>(mut reader: Rs, class_type: &ClassType, size: u16) -> Result<VolumeShellItem,ShellItemError> {
+        let _offset = reader.seek(SeekFrom::Current(0))?;
+        let sub_flags = VolumeItemFlags::from_bits_truncate(
+            class_type.get_minor()
+        );
+        let mut name = None;
+        let mut sort_index = None;
+        let mut guid = None;
+        let mut guid_name = None;
+        let mut extension_blocks = None;
+
+        if class_type.get_type() == 0x2E {
+            // Extended volume item with a shell folder identifier
+            sort_index = Some(reader.read_u8()?);
+            let folder_guid = Guid::new(&mut reader)?;
+            guid_name = root_folder_shell::get_shell_folder_name(&folder_guid);
+            guid = Some(folder_guid);
+
+            // size(2), class_type(1), sort_index(1) and guid(16) make up 20 bytes
+            if size > 20 {
+                let mut extension_list = ExtensionList::new(&mut reader, size as u64 - 20)?;
+                extension_list.check_version_offsets(20);
+                extension_blocks = Some(extension_list);
+            }
+        } else {
+            // subtract 3 from size to account for size(2) and class_type(1)
+            let mut buffer = vec![0; size.saturating_sub(3) as usize];
+            reader.read_exact(&mut buffer)?;
+
+            if sub_flags.contains(HAS_NAME) {
+                // Volume name is an ascii string stored in a 20 byte field
+                let name_size = if buffer.len() < 20 { buffer.len() } else { 20 };
+                name = Some(
+                    utils::decode_ascii(&buffer[..name_size])?
+                );
+            }
+        }
+
+        Ok(
+            VolumeShellItem {
+                _offset: _offset,
+                sub_flags: sub_flags,
+                name: name,
+                sort_index: sort_index,
+                guid: guid,
+                guid_name: guid_name,
+                extension_blocks: extension_blocks
+            }
+        )
+    }
+}
+
+#[test]
+fn test_volume_item() {
+    use std::io::Cursor;
+    let buffer: &[u8] = &[
+        0x43,0x3A,0x5C,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+        0x00,0x00,0x00,0x00,0x00,0x00
+    ];
+
+    let volume = VolumeShellItem::new(
+        Cursor::new(buffer),
+        &ClassType::new(0x2F),
+        0x19
+    ).unwrap();
+    assert_eq!(volume.name,Some(String::from("C:\\")));
+    assert!(volume.sub_flags.contains(HAS_NAME));
+}